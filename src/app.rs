@@ -1,20 +1,72 @@
+mod board;
+mod config;
+mod dates;
+mod emergency;
+mod error;
+mod guard;
+mod history;
+mod keymap;
+mod merge;
+mod priority;
+mod registers;
 mod serialisation;
 mod task;
+mod task_file;
+mod theme;
+mod viewport;
+
+pub use crate::app::config::load_config;
+pub use crate::app::error::TasksError;
+pub use crate::app::guard::TerminalGuard;
+pub use crate::app::serialisation::{
+    deserialise, new_tasks_data, recover_file, recovery_path, serialise, set_aside, temp_path,
+    DEFAULT_FILE_NAME,
+};
 
-pub use crate::app::serialisation::{deserialise, new_tasks_data, serialise};
-
-use crate::app::task::{List, Task};
+pub use crate::app::task::{List, Task};
+pub use crate::app::task_file::TaskFile;
+
+use crate::app::board::{layout_column, truncate, visible_width, wrap, Board, COLUMN_SEPARATOR};
+use crate::app::config::{Autosave, Config};
+use crate::app::dates::{parse_date_input, today};
+use crate::app::emergency::{emergency_save, save_on_exit, Unsaved};
+use crate::app::history::{History, Snapshot};
+use crate::app::keymap::{Action, Input, Key, Keymap, Mode, PendingInput};
+use crate::app::merge::merge;
+use crate::app::priority::Priority;
+use crate::app::registers::Registers;
+use crate::app::task::SortKey;
+use crate::app::theme::{set_theme, theme};
+use crate::app::viewport::Viewport;
 
 use colored::Colorize;
 use crossterm::{
-    cursor::{self, RestorePosition, SavePosition},
-    event::{read, Event, KeyCode},
+    cursor,
+    event::{poll, read, Event, KeyCode},
     execute,
     style::Print,
-    terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType},
+    terminal::{self, Clear, ClearType},
     Result,
 };
-use std::io::stdout;
+use std::fmt;
+use std::io::{self, stdout};
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// How often the task file is checked for changes made to it outside the app
+const FILE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a number typed on its own waits for a keybind to give a count to, before it moves to
+/// the list with that number instead
+const LIST_NUMBER_DELAY: Duration = Duration::from_secs(1);
+
+/// How many columns the bar showing how much of the current list is completed takes up
+const PROGRESS_BAR_WIDTH: usize = 10;
+
+/// What is drawn between the parts of the status line
+const STATUS_SEPARATOR: &str = "  ";
 
 /// Prints a string followed by a new line and carriage return to the stdout using Crossterm.
 /// Works in raw mode
@@ -44,12 +96,89 @@ fn get_confirmation() -> Result<bool> {
     }
 }
 
+/// What the app should do after carrying out an action
+enum Flow {
+    /// Wait for the next action
+    Continue,
+    /// Exit the app, saving the changes made if `save` is true
+    Quit { save: bool },
+}
+
+/// One of the tasks drawn on the screen that the cursor can jump to
+enum ScreenRow {
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// A tag that the shown tasks are being filtered by
+#[derive(Clone)]
+struct Filter {
+    tag: String,
+    /// The index of the list being filtered, or None if every list is
+    list: Option<usize>,
+}
+
+/// A message shown in place of the status line until the next key is pressed
+#[derive(Clone, Debug)]
+enum Message {
+    /// What an action did, such as how many tasks it deleted
+    Info(String),
+    /// Why an action couldn't be carried out
+    Error(String),
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Message::Info(text) => write!(f, "{}", text),
+            Message::Error(text) => write!(f, "[{}] {}", "!".bright_red(), text),
+        }
+    }
+}
+
 /// The application
 pub struct TasksApp {
     lists: Vec<List>,
+    /// The file the lists are read from and saved to
+    file: TaskFile,
+    /// The changes that haven't been saved yet, which are saved to a recovery file if the app is
+    /// killed or panics
+    unsaved: Unsaved,
+    /// When the lists are saved without quitting
+    autosave: Autosave,
+    /// How long to wait without a key being pressed before saving, when autosaving when idle
+    autosave_delay: Duration,
+    /// When the last key was pressed
+    last_key: Instant,
     current_list_index: usize,
     current_task_index: usize,
-    clipboard: Vec<Task>,
+    registers: Registers,
+    /// The register named for the command being carried out, if one was
+    register: Option<char>,
+    history: History,
+    viewport: Viewport,
+    filter: Option<Filter>,
+    /// The text being searched for, if any
+    search: Option<String>,
+    keymap: Keymap,
+    /// The keys typed so far that don't make up a whole command yet
+    pending: PendingInput,
+    /// The last action that changed the lists and the count it was given, for repeating with `.`
+    last_change: Option<(Action, Option<usize>)>,
+    /// The index into the shown tasks that the selection was started from, if tasks are being
+    /// selected
+    visual: Option<usize>,
+    /// A message shown in place of the status line until the next key is pressed, such as how
+    /// many tasks were deleted or why an action failed
+    message: Option<Message>,
+    /// The state of the board view, if every list is being shown side by side
+    board: Option<Board>,
+    /// Whether the app takes up the whole terminal, drawn in the alternate screen with the list
+    /// tabs along the top and a status line along the bottom
+    full_screen: bool,
+    /// The row of the terminal that the app is drawn from
+    origin_row: u16,
 }
 
 impl TasksApp {
@@ -57,345 +186,1782 @@ impl TasksApp {
     ///
     /// # Arguments
     ///
-    /// * `lists` - The lists the app should have to start with
-    pub fn new(lists: Vec<List>) -> Self {
+    /// * `file`   - The file the lists are read from and saved to
+    /// * `config` - The settings read from the config file
+    pub fn new(file: TaskFile, config: &Config) -> Self {
+        set_theme(config.theme());
+
         Self {
-            lists,
+            lists: file.base().to_vec(),
+            file,
+            unsaved: Unsaved::default(),
+            autosave: config.autosave(),
+            autosave_delay: config.autosave_delay(),
+            last_key: Instant::now(),
             current_list_index: 0,
             current_task_index: 0,
-            clipboard: vec![],
+            registers: Registers::default(),
+            register: None,
+            history: History::default(),
+            viewport: Viewport::default(),
+            filter: None,
+            search: None,
+            keymap: config.keymap(),
+            pending: PendingInput::default(),
+            last_change: None,
+            visual: None,
+            message: None,
+            board: None,
+            full_screen: config.full_screen(),
+            origin_row: 0,
         }
     }
 
-    /// Runs the program
-    ///
-    /// # Returns
-    ///
-    /// The finished state of the lists after the program has run
-    pub fn run(&mut self) -> Result<Option<Vec<List>>> {
-        // Saving the start position of the app
-        execute!(
-            stdout(),
-            SavePosition,
-            cursor::SetCursorStyle::SteadyUnderScore
-        )?;
-        enable_raw_mode()?;
+    /// Runs the program, saving the lists to the task file when it is quit unless the changes are
+    /// thrown away. If the app has to stop because of an error, the changes that weren't saved are
+    /// written to the recovery file as they would be if it crashed
+    pub fn run(&mut self) -> Result<()> {
+        save_on_exit(self.file.path(), &self.unsaved)?;
+        let result = self.run_until_quit();
+        if result.is_err() {
+            emergency_save(self.file.path(), &self.unsaved);
+        }
+        result
+    }
 
-        let mut save_changes = true;
+    /// Draws the app and handles keys until it is quit, then saves the lists if they should be
+    fn run_until_quit(&mut self) -> Result<()> {
+        let guard = TerminalGuard::new(self.full_screen)?;
+        execute!(stdout(), cursor::SetCursorStyle::SteadyUnderScore)?;
+        // Saving the start position of the app, which is the top of the screen when it has the
+        // whole terminal
+        self.origin_row = match self.full_screen {
+            true => 0,
+            false => cursor::position()?.1,
+        };
 
-        loop {
-            execute!(
-                stdout(),
-                RestorePosition,
-                Clear(ClearType::FromCursorDown),
-                cursor::SetCursorStyle::SteadyUnderScore
-            )?;
-            self.draw(&self.lists[self.current_list_index])?;
-            execute!(
-                stdout(),
-                RestorePosition,
-                cursor::MoveDown((self.current_task_index + 1) as u16),
-                cursor::MoveRight(1)
-            )?;
+        let save_changes = loop {
+            self.refresh()?;
 
-            if self.lists[self.current_list_index].length() == 0 {
-                execute!(stdout(), cursor::Hide)?;
-            } else {
-                execute!(stdout(), cursor::Show)?;
-            }
-
-            if let Event::Key(key) = read()? {
-                match key.code {
-                    KeyCode::Down | KeyCode::Char('j') => self.move_to_next_task(),
-                    KeyCode::Up | KeyCode::Char('k') => self.move_to_prev_task(),
-                    KeyCode::Right | KeyCode::Char('l') => self.move_to_next_list(),
-                    KeyCode::Char('L') => self.move_current_task_to_next_list(),
-                    KeyCode::Left | KeyCode::Char('h') => self.move_to_prev_list(),
-                    KeyCode::Char('H') => self.move_current_task_to_prev_list(),
-                    KeyCode::Char('p') => self.paste_clipboard(1),
-                    KeyCode::Char('P') => self.paste_clipboard(0),
-                    KeyCode::Char('D') => self.delete_current_list()?,
-                    KeyCode::Char('d') => {
-                        if let Event::Key(key) = read()? {
-                            match key.code {
-                                KeyCode::Char('d') => self.delete_current_task(),
-                                KeyCode::Char('A') => self.delete_all_tasks(),
-                                KeyCode::Char('c') => self.delete_completed_tasks(),
-                                KeyCode::Char('C') => self.delete_completed_tasks_on_all_lists(),
-                                _ => (),
-                            }
-                        }
-                    }
-                    KeyCode::Char('y') => {
-                        if let Event::Key(key) = read()? {
-                            match key.code {
-                                KeyCode::Char('y') => self.yank_current_task(),
-                                KeyCode::Char('A') => self.yank_current_list(),
-                                _ => (),
-                            }
-                        }
-                    }
-                    KeyCode::Char('N') => self.create_new_list()?,
-                    KeyCode::Char('n') => self.create_new_task()?,
-                    KeyCode::Char('r') => self.reword_current_task()?,
-                    KeyCode::Char('R') => self.rename_current_list()?,
-                    KeyCode::Char('s') => self.sort_current_list(),
-                    KeyCode::Char('S') => self.sort_all_lists(),
-                    KeyCode::Char('G') => self.goto_bottom(),
-                    KeyCode::Char('?') => self.draw_help()?,
-                    KeyCode::Char(' ') => {
-                        self.lists[self.current_list_index].toggle_task(self.current_task_index)
-                    }
-                    KeyCode::Char('q') => break,
-                    KeyCode::Char('Q') => {
-                        self.goto_empty_line()?;
-                        execute!(
-                            stdout(),
-                            Print(format!(
-                                "[{}] This will exit without saving, are you sure? y/N ",
-                                "!".bright_red()
-                            ))
-                        )?;
-
-                        if !get_confirmation()? {
-                            continue;
-                        }
-
-                        save_changes = false;
-                        break;
-                    }
-                    KeyCode::Char('1') => self.move_to_list(0),
-                    KeyCode::Char('2') => self.move_to_list(1),
-                    KeyCode::Char('3') => self.move_to_list(2),
-                    KeyCode::Char('4') => self.move_to_list(3),
-                    KeyCode::Char('5') => self.move_to_list(4),
-                    KeyCode::Char('6') => self.move_to_list(5),
-                    KeyCode::Char('7') => self.move_to_list(6),
-                    KeyCode::Char('8') => self.move_to_list(7),
-                    KeyCode::Char('9') => self.move_to_list(8),
-                    _ => (),
+            let key = match self.next_event()? {
+                Event::Key(key) => key,
+                Event::Resize(_, rows) => {
+                    self.origin_row = self.origin_row.min(rows.saturating_sub(1));
+                    continue;
                 }
+                _ => continue,
+            };
+            self.message = None;
+            self.last_key = Instant::now();
+
+            let mode = match self.visual {
+                Some(_) => Mode::Visual,
+                None => Mode::Normal,
+            };
+            let (action, count) = match self.pending.push(&self.keymap, mode, Key::from(key)) {
+                Input::Command(action, count, register) => {
+                    self.register = register;
+                    (action, count)
+                }
+                Input::Pending | Input::Unbound => continue,
+            };
+
+            let before = self.snapshot();
+            // Changes made to a selection are repeated on the same number of tasks
+            let change_count = self.selected_rows().map(|rows| rows.count()).or(count);
+
+            match self.perform(action, count)? {
+                Flow::Continue => (),
+                Flow::Quit { save: false } => break false,
+                Flow::Quit { save: true } => match self.prepare_to_save()? {
+                    true => break true,
+                    false => continue,
+                },
             }
-        }
+            self.clamp_cursor();
+
+            // Undoing and redoing move through the history rather than adding to it
+            let moves_through_history = matches!(action, Action::Undo | Action::Redo);
+            if !moves_through_history && before.lists != self.lists {
+                self.history.record(before);
+                // What was typed into a prompt isn't kept, so `.` does nothing after those changes
+                // rather than opening the prompt again
+                self.last_change = match action {
+                    Action::RepeatLastChange => self.last_change,
+                    action if action.asks_for_input() => None,
+                    action => Some((action, change_count)),
+                };
+            }
+            self.update_unsaved();
+            if self.autosave == Autosave::Change {
+                self.save()?;
+            }
+        };
 
-        disable_raw_mode()?;
         execute!(
             stdout(),
-            cursor::SetCursorStyle::DefaultUserShape,
-            cursor::Show,
-            RestorePosition,
+            self.move_to_origin(),
             Clear(ClearType::FromCursorDown)
         )?;
+        drop(guard);
+
+        if save_changes {
+            self.file.save(&self.lists).map_err(|e| {
+                io::Error::other(format!(
+                    "Couldn't write {}: {}",
+                    self.file.path().display(),
+                    e
+                ))
+            })?;
+        }
+        Ok(())
+    }
 
-        match save_changes {
-            true => Ok(Some(self.lists.clone())),
-            false => Ok(None),
+    /// Keeps a copy of the lists where they can be saved if the app is killed or panics, if they
+    /// have changed since the task file was last read or saved
+    fn update_unsaved(&self) {
+        let changed = self.lists != self.file.base();
+        self.unsaved
+            .set(changed.then(|| self.file.document(&self.lists)));
+    }
+
+    /// Draws the app along with any message, leaving the cursor on the current task
+    fn refresh(&mut self) -> Result<()> {
+        self.redraw()?;
+        self.draw_message()?;
+        let (column, row) = self.cursor_position();
+        execute!(stdout(), cursor::MoveTo(column, row))?;
+
+        if self.lists[self.current_list_index].length() == 0 {
+            execute!(stdout(), cursor::Hide)?;
+        } else {
+            execute!(stdout(), cursor::Show)?;
         }
+        Ok(())
     }
 
-    /// Draws the given list of the app
+    /// Waits for the next event, checking whether the task file has been changed outside the app
+    /// while waiting
+    fn next_event(&mut self) -> Result<Event> {
+        loop {
+            let timeout = match self.lone_count() {
+                Some(_) => LIST_NUMBER_DELAY
+                    .saturating_sub(self.last_key.elapsed())
+                    .min(FILE_CHECK_INTERVAL),
+                None => FILE_CHECK_INTERVAL,
+            };
+            if poll(timeout)? {
+                return read();
+            }
+
+            if let Some(number) = self.lone_count() {
+                if self.last_key.elapsed() >= LIST_NUMBER_DELAY {
+                    self.goto_numbered_list(number);
+                    self.refresh()?;
+                }
+            }
+
+            if self.file.has_changed() {
+                self.sync_with_file()?;
+                self.refresh()?;
+            }
+
+            let idle = self.last_key.elapsed() >= self.autosave_delay;
+            if self.autosave == Autosave::Idle && idle && self.lists != self.file.base() {
+                self.save()?;
+                self.refresh()?;
+            }
+        }
+    }
+
+    /// Moves to the list with the number typed on its own, throwing the number away
     ///
     /// # Arguments
     ///
-    /// * `list` - The list to draw
-    fn draw(&self, list: &List) -> Result<()> {
-        let title = format!(
-            "({}/{}) {}",
-            self.current_list_index + 1,
-            self.lists.len(),
-            list.name(),
-        );
+    /// * `number` - The number of the list, counting from 1
+    fn goto_numbered_list(&mut self, number: usize) {
+        self.pending.clear();
+        self.move_to_list(number - 1);
+        self.clamp_cursor();
+    }
 
-        println(title)?;
-        for task in list.tasks_iter() {
-            println(task.to_string())?;
+    /// Returns the number typed so far if nothing else has been typed with it, which moves to the
+    /// list with that number if no keybind follows it. Lists aren't changed while selecting tasks
+    fn lone_count(&self) -> Option<usize> {
+        match self.visual {
+            Some(_) => None,
+            None => self.pending.lone_count(),
+        }
+    }
+
+    /// Saves the lists without quitting, first bringing in any changes made to the task file
+    /// outside the app. Nothing is written if the lists haven't changed since the file was last
+    /// read or saved
+    fn save(&mut self) -> Result<()> {
+        if self.file.has_changed() {
+            self.sync_with_file()?;
+        }
+        if self.lists == self.file.base() {
+            return Ok(());
+        }
+        // The user is only asked whether to save over a file that couldn't be read when quitting
+        if self.file.is_unreadable() {
+            self.message = Some(Message::Error(format!(
+                "{} was changed outside tasks and can't be read, so it will only be saved over \
+                 when you quit and agree to it",
+                self.file.path().display()
+            )));
+            return Ok(());
         }
 
+        if let Err(e) = self.file.save(&self.lists) {
+            let path = self.file.path().display();
+            self.message = Some(Message::Error(format!("Couldn't save {}: {}", path, e)));
+        }
+        self.update_unsaved();
         Ok(())
     }
 
-    /// Draws the help menu
-    fn draw_help(&self) -> Result<()> {
-        execute!(stdout(), RestorePosition, Clear(ClearType::FromCursorDown))?;
-
-        println(format!("Tasks v{}", env!("CARGO_PKG_VERSION")))?;
-        println("Keybinds")?;
-        let keybinds = vec![
-            "j/k      Move between tasks",
-            "h/l      Move between lists",
-            "H/L      Move current task between lists",
-            "space    Toggle current tasks status",
-            "n        Create new task",
-            "N        Create new list",
-            "r        Reword current task",
-            "R        Rename current list",
-            "dd       Delete current task",
-            "dA       Delete all tasks from the current list",
-            "dc       Delete completed tasks from the current list",
-            "dC       Delete completed tasks from all lists",
-            "D        Delete current list",
-            "yy       Yank current task",
-            "yA       Yank all tasks in the current list",
-            "p        Paste task/s in the clipboard below",
-            "P        Paste task/s in the clipboard above",
-            "s        Sorts the current list",
-            "S        Sorts all lists",
-            "G        Goto to the last task in the list",
-            "1-9      Move to the list corresponding to the number pressed",
-            "?        Show this menu",
-            "q        Quit",
-            "Q        Quit without saving changes",
-        ];
+    /// Gets ready to save the lists when quitting. Any changes made to the task file outside the
+    /// app are brought in first, and if the file was changed in a way that can't be read the user
+    /// is asked whether to save over it
+    ///
+    /// # Returns
+    ///
+    /// Whether the lists should be saved and the app quit
+    fn prepare_to_save(&mut self) -> Result<bool> {
+        if self.file.has_changed() {
+            self.sync_with_file()?;
+        }
+        if !self.file.is_unreadable() {
+            return Ok(true);
+        }
+
+        let path = self.file.path().display().to_string();
+        let message = format!(
+            "[{}] {} was changed outside tasks and can't be read. Save over it? y/N",
+            "!".bright_red(),
+            path
+        );
+        self.goto_empty_line()?;
+        execute!(stdout(), Print(message))?;
 
-        for keybind in keybinds {
-            println(keybind)?;
+        let overwrite = get_confirmation()?;
+        if !overwrite {
+            self.message = Some(Message::Info(format!(
+                "Nothing was saved. Fix {} and quit again, or quit without saving with Q",
+                path
+            )));
         }
+        Ok(overwrite)
+    }
+
+    /// Brings in the changes made to the task file outside the app. If the lists haven't been
+    /// changed since the file was last read or saved, they are replaced with what is in the file.
+    /// Otherwise the user is asked whether to merge the changes with theirs
+    fn sync_with_file(&mut self) -> Result<()> {
+        let base = self.file.base().to_vec();
+        let path = self.file.path().display().to_string();
+
+        let theirs = match self.file.reload() {
+            Ok(lists) => lists,
+            Err(e) => {
+                self.message = Some(Message::Error(format!(
+                    "{} was changed but couldn't be read: {}",
+                    path, e
+                )));
+                return Ok(());
+            }
+        };
+
+        let before = self.snapshot();
+        if self.lists == base {
+            self.lists = theirs;
+            self.message = Some(Message::Info(format!(
+                "Reloaded {}, which was changed outside tasks",
+                path
+            )));
+        } else {
+            let message = format!(
+                "[{}] {} was changed outside tasks. Merge the changes with yours? y/n",
+                "!".bright_red(),
+                path
+            );
+            self.goto_empty_line()?;
+            execute!(stdout(), Clear(ClearType::CurrentLine), Print(message))?;
+
+            let merge_changes = loop {
+                if let Event::Key(key) = read()? {
+                    match key.code {
+                        KeyCode::Char('y') | KeyCode::Char('Y') => break true,
+                        KeyCode::Char('n') | KeyCode::Char('N') => break false,
+                        _ => (),
+                    }
+                }
+            };
+            if !merge_changes {
+                self.message = Some(Message::Info(format!(
+                    "Kept your changes, saving will overwrite the ones made to {}",
+                    path
+                )));
+                return Ok(());
+            }
 
-        println("\nPress any key to return")?;
-        read()?;
+            let merged = merge(&base, &self.lists, &theirs);
+            if merged.lists.is_empty() {
+                self.message = Some(Message::Error(
+                    "Merging the changes would leave no lists".to_string(),
+                ));
+                return Ok(());
+            }
+            self.lists = merged.lists;
+            self.message = Some(Message::Info(match merged.conflicts {
+                0 => format!("Merged the changes made to {}", path),
+                n => format!(
+                    "Merged the changes made to {}, keeping yours for {} task{} changed in both",
+                    path,
+                    n,
+                    if n == 1 { "" } else { "s" }
+                ),
+            }));
+        }
 
+        if before.lists != self.lists {
+            self.history.record(before);
+        }
+        self.update_unsaved();
+        self.visual = None;
+        self.clamp_cursor();
         Ok(())
     }
 
-    // Moves the current_list_index to the next list
-    fn move_to_next_list(&mut self) {
-        if self.current_list_index + 1 < self.lists.len() {
-            self.current_list_index += 1;
-            self.current_task_index = 0;
+    /// Carries out the given action, using the count typed before it if there was one
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action to carry out
+    /// * `count`  - The count typed before the action
+    ///
+    /// # Returns
+    ///
+    /// Whether the app should keep running
+    fn perform(&mut self, action: Action, count: Option<usize>) -> Result<Flow> {
+        if action.needs_task() && self.current_index().is_none() {
+            let message = match self.lists[self.current_list_index].length() {
+                0 => "This list has no tasks",
+                _ => "No tasks are shown",
+            };
+            self.message = Some(Message::Info(message.to_string()));
+            return Ok(Flow::Continue);
+        }
+
+        match (action, count) {
+            (Action::GotoTop | Action::GotoBottom, Some(count)) => self.goto_row(count - 1),
+            (Action::GotoList, Some(count)) => self.move_to_list(count - 1),
+            // Counted deletes and yanks work on that many tasks, the same as selecting them
+            (Action::DeleteTask, Some(count)) => {
+                self.select_rows(count);
+                self.delete_selection();
+            }
+            (Action::YankTask, Some(count)) => {
+                self.select_rows(count);
+                self.yank_selection();
+            }
+            (Action::RepeatLastChange, _) => {
+                if let Some((action, last_count)) = self.last_change {
+                    let count = count.or(last_count);
+                    if action.acts_on_selection() && self.visual.is_none() {
+                        self.select_rows(count.unwrap_or(1));
+                    }
+                    return self.perform(action, count);
+                }
+            }
+            (Action::PasteBelow, Some(count)) => self.paste_clipboard(1, count),
+            (Action::PasteAbove, Some(count)) => self.paste_clipboard(0, count),
+            (action, Some(count)) if action.repeats_with_count() => {
+                for _ in 0..count {
+                    self.perform_once(action)?;
+                }
+            }
+            (action, _) => return self.perform_once(action),
         }
+        Ok(Flow::Continue)
     }
 
-    // Moves the current_list_index to the previous list
-    fn move_to_prev_list(&mut self) {
-        self.current_list_index = self.current_list_index.saturating_sub(1);
-        if self.current_task_index >= self.lists[self.current_list_index].length() {
-            self.current_task_index = 0;
+    /// Carries out the given action a single time
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action to carry out
+    ///
+    /// # Returns
+    ///
+    /// Whether the app should keep running
+    fn perform_once(&mut self, action: Action) -> Result<Flow> {
+        match action {
+            Action::NextTask => self.move_to_next_task(),
+            Action::PrevTask => self.move_to_prev_task(),
+            Action::NextList => self.move_to_next_list(),
+            Action::PrevList => self.move_to_prev_list(),
+            Action::GotoList => self.move_to_next_list(),
+            Action::GotoTop => self.goto_row(0),
+            Action::GotoBottom => self.goto_bottom(),
+            Action::GotoScreenTop => self.goto_screen_row(ScreenRow::Top),
+            Action::GotoScreenMiddle => self.goto_screen_row(ScreenRow::Middle),
+            Action::GotoScreenBottom => self.goto_screen_row(ScreenRow::Bottom),
+            Action::MoveListLeft => self.move_current_list_left(),
+            Action::MoveListRight => self.move_current_list_right(),
+            Action::ManageLists => self.manage_lists()?,
+            Action::ToggleBoard => self.toggle_board(),
+            Action::Visual => self.toggle_visual(),
+            Action::YankSelection => self.yank_selection(),
+            Action::DeleteSelection => self.delete_selection(),
+            Action::ToggleSelection => self.toggle_selection(),
+            Action::MoveSelectionToNextList => self.move_selection_to_list(true),
+            Action::MoveSelectionToPrevList => self.move_selection_to_list(false),
+            Action::PasteOverSelection => self.paste_over_selection(),
+            Action::MoveTaskToNextList => self.move_current_task_to_next_list(),
+            Action::MoveTaskToPrevList => self.move_current_task_to_prev_list(),
+            Action::MoveTaskDown => self.move_current_task_down(),
+            Action::MoveTaskUp => self.move_current_task_up(),
+            Action::ToggleTask => self.toggle_current_task()?,
+            Action::NewTask => self.create_new_task()?,
+            Action::NewList => self.create_new_list()?,
+            Action::RewordTask => self.reword_current_task()?,
+            Action::RenameList => self.rename_current_list()?,
+            Action::DeleteTask => self.delete_current_task(),
+            Action::DeleteAllTasks => self.delete_all_tasks(),
+            Action::DeleteCompletedTasks => self.delete_completed_tasks(),
+            Action::DeleteCompletedTasksOnAllLists => self.delete_completed_tasks_on_all_lists(),
+            Action::DeleteList => self.delete_current_list()?,
+            Action::YankTask => self.yank_current_task(),
+            Action::YankList => self.yank_current_list(),
+            Action::PasteBelow => self.paste_clipboard(1, 1),
+            Action::PasteAbove => self.paste_clipboard(0, 1),
+            Action::IndentTask => self.indent_current_task(),
+            Action::OutdentTask => self.outdent_current_task(),
+            Action::ToggleFold => self.toggle_current_fold(),
+            Action::FoldAll => self.set_current_list_folded(true),
+            Action::UnfoldAll => self.set_current_list_folded(false),
+            Action::SetDueDate => self.set_current_task_due()?,
+            Action::RaisePriority => self.raise_current_task_priority(),
+            Action::LowerPriority => self.lower_current_task_priority(),
+            Action::SortList => {
+                if let Some(key) = self.read_sort_key()? {
+                    self.sort_current_list(key);
+                    let message = format!("Sorted the list by {}", key.name());
+                    self.message = Some(Message::Info(message));
+                }
+            }
+            Action::SortAllLists => {
+                if let Some(key) = self.read_sort_key()? {
+                    self.sort_all_lists(key);
+                    let message = format!("Sorted every list by {}", key.name());
+                    self.message = Some(Message::Info(message));
+                }
+            }
+            Action::FilterList => self.filter_by_tag(false)?,
+            Action::FilterAllLists => self.filter_by_tag(true)?,
+            Action::Search => self.search()?,
+            Action::NextMatch => self.goto_match(true),
+            Action::PrevMatch => self.goto_match(false),
+            Action::RepeatLastChange => (),
+            Action::Undo => self.undo(),
+            Action::Redo => self.redo(),
+            Action::Help => self.draw_help()?,
+            Action::Quit => return Ok(Flow::Quit { save: true }),
+            Action::QuitWithoutSaving => {
+                self.goto_empty_line()?;
+                execute!(
+                    stdout(),
+                    Print(format!(
+                        "[{}] This will exit without saving, are you sure? y/N ",
+                        "!".bright_red()
+                    ))
+                )?;
+
+                if get_confirmation()? {
+                    return Ok(Flow::Quit { save: false });
+                }
+            }
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    /// Takes a snapshot of the current state of the app for the history
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            lists: self.lists.clone(),
+            current_list_index: self.current_list_index,
+            current_task_index: self.current_task_index,
         }
     }
 
-    /// Moves to the given list if it exists
+    /// Restores the app to the state in the given snapshot
     ///
     /// # Arguments
     ///
-    /// * `index` - The index of the list to move to
-    fn move_to_list(&mut self, index: usize) {
-        if index < self.lists.len() {
-            self.current_list_index = index;
+    /// * `snapshot` - The state to restore
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.lists = snapshot.lists;
+        self.current_list_index = snapshot
+            .current_list_index
+            .min(self.lists.len().saturating_sub(1));
+        self.current_task_index = snapshot.current_task_index;
+        self.clamp_cursor();
+    }
+
+    /// Undoes the last change made to the lists, if there is one
+    fn undo(&mut self) {
+        match self.history.undo(self.snapshot()) {
+            Some(snapshot) => self.restore(snapshot),
+            None => self.message = Some(Message::Info("Already at the oldest change".to_string())),
         }
     }
 
-    /// Moves the task cursor down
-    fn move_to_next_task(&mut self) {
-        if self.current_task_index + 1 < self.lists[self.current_list_index].length() {
-            self.current_task_index += 1;
+    /// Redoes the last change that was undone, if there is one
+    fn redo(&mut self) {
+        match self.history.redo(self.snapshot()) {
+            Some(snapshot) => self.restore(snapshot),
+            None => self.message = Some(Message::Info("Already at the newest change".to_string())),
         }
     }
 
-    /// Moves the task cursor up
-    fn move_to_prev_task(&mut self) {
-        self.current_task_index = self.current_task_index.saturating_sub(1);
+    /// Draws the message in place of the status line, if there is one
+    fn draw_message(&mut self) -> Result<()> {
+        if let Some(message) = &self.message {
+            // A message wrapping past the status line would scroll the terminal
+            let message = cut_short(&message.to_string(), terminal::size()?.0 as usize);
+            self.goto_empty_line()?;
+            execute!(stdout(), Print(message))?;
+        }
+        Ok(())
     }
 
-    /// Gets the current task that the current_task_index is pointing to
-    fn get_current_task(&self) -> Option<Task> {
-        self.lists[self.current_list_index]
-            .tasks_iter()
-            .map(|x| x.to_owned())
-            .nth(self.current_task_index)
+    /// Shows the error in place of the status line if the result is one
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The result of an action
+    ///
+    /// # Returns
+    ///
+    /// The value of the result if it isn't an error
+    fn report<T>(&mut self, result: std::result::Result<T, TasksError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.message = Some(Message::Error(e.to_string()));
+                None
+            }
+        }
     }
 
-    /// Moves the current task to the next list, if there is one
-    fn move_current_task_to_next_list(&mut self) {
-        if self.current_list_index + 1 >= self.lists.len() {
-            return;
+    /// Clears the app and draws the current list, or every list if the board is shown
+    fn redraw(&mut self) -> Result<()> {
+        if self.board.is_some() {
+            self.draw_board()?;
+        } else {
+            self.update_viewport()?;
+            execute!(
+                stdout(),
+                self.move_to_origin(),
+                Clear(ClearType::FromCursorDown),
+                cursor::SetCursorStyle::SteadyUnderScore
+            )?;
+            self.draw(&self.lists[self.current_list_index])?;
         }
+        self.draw_status_line()
+    }
 
-        let task = match self.get_current_task() {
-            Some(task) => task,
-            None => return,
+    /// Draws the given list of the app
+    ///
+    /// # Arguments
+    ///
+    /// * `list` - The list to draw
+    fn draw(&self, list: &List) -> Result<()> {
+        let width = terminal::size()?.0 as usize;
+        // With the whole terminal the lists are shown as tabs
+        let title = match self.full_screen {
+            true => list_tabs(&self.lists, self.current_list_index, width as u16),
+            false => format!(
+                "({}/{}) {}",
+                self.current_list_index + 1,
+                self.lists.len(),
+                list.name(),
+            ),
         };
 
-        self.lists[self.current_list_index].delete_task(self.current_task_index);
-        self.move_to_next_list();
-        self.current_task_index = self.lists[self.current_list_index].length();
-        self.lists[self.current_list_index].insert_task(self.current_task_index, task);
+        // Every line is kept to a single row, as the viewport and the rows below it are worked
+        // out from one row for each task
+        println(cut_short(&title, width))?;
+
+        if self.viewport.is_scrolling() {
+            println(hidden_tasks_message(self.viewport.hidden_above(), "above"))?;
+        }
+        let visible = self.visible_tasks();
+        for row in self.viewport.visible() {
+            let line = self.task_line(self.current_list_index, visible[row], row);
+            println(cut_short(&line, width))?;
+        }
+        if self.viewport.is_scrolling() {
+            println(hidden_tasks_message(self.viewport.hidden_below(), "below"))?;
+        }
+
+        Ok(())
     }
 
-    /// Moves the current task to the list previous to the current one, if there is one
-    fn move_current_task_to_prev_list(&mut self) {
-        if self.current_list_index == 0 {
-            return;
+    /// Returns what is hidden or selected in the given list, such as `[#bug, 2 hidden]`
+    ///
+    /// # Arguments
+    ///
+    /// * `list` - The current list
+    fn list_details(&self, list: &List) -> Vec<String> {
+        let mut details = vec![];
+        if let Some(filter) = self.active_filter() {
+            let hidden = list.unfolded_indexes().len() - self.visible_tasks().len();
+            details.push(format!("[{}, {} hidden]", filter.tag, hidden));
         }
+        if let Some(rows) = self.selected_rows() {
+            details.push(format!("[{} selected]", rows.count()));
+        }
+        details
+    }
 
-        let task = match self.get_current_task() {
-            Some(task) => task,
-            None => return,
+    /// Draws the status line below the tasks, which shows the mode, the keys typed so far, how
+    /// many tasks are completed, what is in the clipboard and whether there are unsaved changes.
+    /// The least useful parts are left out when the terminal is too narrow for all of them
+    fn draw_status_line(&self) -> Result<()> {
+        let width = terminal::size()?.0 as usize;
+        let list = &self.lists[self.current_list_index];
+
+        // Each part is paired with how important it is to keep, 0 being the most important
+        let mut parts: Vec<(usize, String)> = vec![];
+        let mode = match (self.visual, self.board) {
+            (Some(_), _) => " VISUAL ",
+            (None, Some(_)) => " BOARD ",
+            (None, None) => " NORMAL ",
         };
+        parts.push((0, mode.bold().reversed().to_string()));
 
-        self.lists[self.current_list_index].delete_task(self.current_task_index);
-        self.move_to_prev_list();
-        self.current_task_index = self.lists[self.current_list_index].length();
-        self.lists[self.current_list_index].insert_task(self.current_task_index, task);
+        let pending = self.pending.to_string();
+        if !pending.is_empty() {
+            parts.push((1, pending.bold().to_string()));
+        }
+        if self.lists != self.file.base() {
+            parts.push((2, "[+] unsaved".bold().to_string()));
+        }
+
+        let (completed, total) = (list.completed_count(), list.length());
+        parts.push((
+            3,
+            format!(
+                "{}/{} done {}",
+                completed,
+                total,
+                progress_bar(completed, total, PROGRESS_BAR_WIDTH)
+            ),
+        ));
+        for detail in self.list_details(list) {
+            parts.push((4, detail));
+        }
+        if self.lists.len() > 1 {
+            let completed: usize = self.lists.iter().map(List::completed_count).sum();
+            let total: usize = self.lists.iter().map(List::length).sum();
+            parts.push((5, format!("{}/{} in all lists", completed, total)));
+        }
+        if let Some(clipboard) = clipboard_summary(self.registers.last()) {
+            parts.push((6, clipboard.dimmed().to_string()));
+        }
+        if self.board.is_none() && self.current_index().is_some() {
+            let position = format!(
+                "Task {}/{}",
+                self.current_task_index + 1,
+                self.visible_tasks().len()
+            );
+            parts.push((7, position));
+        }
+
+        while parts.len() > 1 && status_width(&parts) > width {
+            let least_important = (0..parts.len())
+                .max_by_key(|i| parts[*i].0)
+                .unwrap_or_default();
+            parts.remove(least_important);
+        }
+
+        let line = parts
+            .into_iter()
+            .map(|(_, part)| part)
+            .collect::<Vec<String>>()
+            .join(STATUS_SEPARATOR);
+        // The hints are right aligned, and only shown if there is room for them
+        let hints = "? help  q quit ";
+        let line = match visible_width(&line) + hints.len() + STATUS_SEPARATOR.len() <= width {
+            true => format!("{}{}", pad(&line, width - hints.len()), hints.dimmed()),
+            false => cut_short(&line, width),
+        };
+
+        execute!(
+            stdout(),
+            cursor::MoveTo(0, self.status_row()?),
+            Clear(ClearType::CurrentLine),
+            Print(line)
+        )
     }
 
-    /// Creates a new list
-    fn create_new_list(&mut self) -> Result<()> {
-        execute!(stdout(), RestorePosition, Clear(ClearType::FromCursorDown))?;
-        let prompt = format!(
-            "({}/{}) ",
-            self.current_list_index + 2,
-            self.lists.len() + 1
-        );
+    /// Returns the row of the terminal the status line is drawn on, which is also where prompts
+    /// and messages are shown. When the app takes up the whole terminal this is the bottom row,
+    /// otherwise it is the row below the tasks
+    fn status_row(&self) -> Result<u16> {
+        Ok(match self.full_screen {
+            true => terminal::size()?.1.saturating_sub(1),
+            false => self.origin_row + 1 + self.app_height() as u16,
+        })
+    }
 
-        let prompt_length = prompt.len() as u16;
+    /// Returns a task as it should be drawn, with the search highlighted, the number of subtasks
+    /// hidden if it is folded, and in reverse video if it is selected
+    ///
+    /// # Arguments
+    ///
+    /// * `list_index` - The index of the list the task is in
+    /// * `index`      - The index of the task in the list
+    /// * `row`        - The index of the task in the shown tasks of the list
+    fn task_line(&self, list_index: usize, index: usize, row: usize) -> String {
+        let list = &self.lists[list_index];
+        let task = match list.tasks_iter().nth(index) {
+            Some(task) => task,
+            None => return String::new(),
+        };
 
-        let name = typing_line(prompt, prompt_length, String::new())?;
+        let mut line = match &self.search {
+            Some(pattern) => task.highlighted(pattern),
+            None => task.to_string(),
+        };
+        if task.is_folded() {
+            let count = format!("(+{})", list.subtask_count(index));
+            line.push_str(&format!(" {}", count.dimmed()));
+        }
 
-        if let Some(name) = name {
-            if let Ok(list) = List::new(name) {
-                self.lists.insert(self.current_list_index + 1, list);
-                self.current_list_index += 1;
-            }
+        let selected = list_index == self.current_list_index
+            && self.selected_rows().is_some_and(|rows| rows.contains(&row));
+        match selected {
+            // Styles inside the line end with a reset, so reverse video is turned back on after them
+            true => format!(
+                "\x1b[7m{}\x1b[0m",
+                line.replace("\x1b[0m", "\x1b[0m\x1b[7m")
+            ),
+            false => line,
         }
-        Ok(())
     }
 
-    /// Renames the current list
-    fn rename_current_list(&mut self) -> Result<()> {
-        execute!(stdout(), RestorePosition,)?;
+    /// Clears the app and draws every list side by side as columns
+    fn draw_board(&mut self) -> Result<()> {
+        let (width, height) = terminal::size()?;
+        // One row is used for the titles, and another is kept free for prompts
+        let rows = height.saturating_sub(2).max(1) as usize;
+
+        let mut board = self.board.unwrap_or_default();
+        let (shown, column_width) =
+            board.columns(self.current_list_index, self.lists.len(), width as usize);
+
+        let mut columns = vec![];
+        let mut cursor = None;
+        for (x, list_index) in shown.clone().enumerate() {
+            let list = &self.lists[list_index];
+            let lines: Vec<(String, usize)> = self
+                .visible_tasks_in(list_index)
+                .into_iter()
+                .enumerate()
+                .map(|(row, index)| {
+                    let line = self.task_line(list_index, index, row);
+                    (line, list_depth(list, index))
+                })
+                .collect();
+
+            let is_current = list_index == self.current_list_index;
+            let column = layout_column(
+                &lines,
+                is_current.then_some(self.current_task_index),
+                column_width,
+                rows,
+            );
+            if let (true, Some(line)) = (is_current, column.cursor_line) {
+                let depth = lines[self.current_task_index.min(lines.len() - 1)].1;
+                let left = x * (column_width + COLUMN_SEPARATOR.width());
+                cursor = Some(((left + 1 + 2 * depth) as u16, 1 + line as u16));
+            }
+            columns.push(column);
+        }
 
-        let prompt = format!("({}/{}) ", self.current_list_index + 1, self.lists.len());
-        let prompt_length = prompt.len() as u16;
-        let new_name = typing_line(
-            prompt,
-            prompt_length,
+        let board_height = columns
+            .iter()
+            .map(|column| column.lines.len())
+            .max()
+            .unwrap_or_default();
+        board.set_layout(cursor, board_height);
+        self.board = Some(board);
+
+        self.reserve_rows(board_height as u16 + 2)?;
+        execute!(
+            stdout(),
+            self.move_to_origin(),
+            Clear(ClearType::FromCursorDown),
+            cursor::SetCursorStyle::SteadyUnderScore
+        )?;
+
+        let titles: Vec<String> = shown
+            .map(|index| {
+                let title = format!("{}. {}", index + 1, self.lists[index].name());
+                let title = pad(&truncate(&title, column_width), column_width);
+                match index == self.current_list_index {
+                    true => title.bold().to_string(),
+                    false => title,
+                }
+            })
+            .collect();
+        println(titles.join(COLUMN_SEPARATOR).trim_end())?;
+
+        for row in 0..board_height {
+            let line = columns
+                .iter()
+                .map(|column| pad(column.lines.get(row).map_or("", |line| line), column_width))
+                .collect::<Vec<String>>()
+                .join(COLUMN_SEPARATOR);
+            println(line.trim_end())?;
+        }
+
+        Ok(())
+    }
+
+    /// Shows or hides the board, which draws every list side by side
+    fn toggle_board(&mut self) {
+        self.board = match self.board {
+            Some(_) => None,
+            None => Some(Board::default()),
+        };
+    }
+
+    /// Returns where the terminal cursor should be put to show which task is the current one
+    fn cursor_position(&self) -> (u16, u16) {
+        if let Some(board) = &self.board {
+            let (column, row) = board.cursor().unwrap_or((0, 1));
+            return (column, self.origin_row + row);
+        }
+
+        let depth = self
+            .get_current_task()
+            .map(|task| task.depth())
+            .unwrap_or(0);
+        (1 + 2 * depth as u16, self.task_row(self.current_task_index))
+    }
+
+    /// Scrolls the viewport so that the current task is visible, making sure there is enough room
+    /// below the app to draw it
+    fn update_viewport(&mut self) -> Result<()> {
+        // One row is used for the title, and another is kept free for prompts
+        let rows = terminal::size()?.1.saturating_sub(2).max(1) as usize;
+
+        self.viewport
+            .follow(self.current_task_index, self.visible_tasks().len(), rows);
+        self.reserve_rows(self.viewport.height() as u16 + 2)
+    }
+
+    /// Scrolls the terminal if there are not enough rows below the origin of the app
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The number of rows the app needs
+    fn reserve_rows(&mut self, rows: u16) -> Result<()> {
+        let height = terminal::size()?.1;
+        let rows = rows.min(height);
+
+        if self.origin_row + rows > height {
+            let overflow = self.origin_row + rows - height;
+            execute!(
+                stdout(),
+                cursor::MoveTo(0, height.saturating_sub(1)),
+                Print("\n".repeat(overflow as usize))
+            )?;
+            self.origin_row -= overflow;
+        }
+        Ok(())
+    }
+
+    /// Returns the command to move the cursor to the first row of the app
+    fn move_to_origin(&self) -> cursor::MoveTo {
+        cursor::MoveTo(0, self.origin_row)
+    }
+
+    /// Returns the terminal row that the task at the given index is drawn on
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the task
+    fn task_row(&self, index: usize) -> u16 {
+        self.origin_row + 1 + self.viewport.row_of(index) as u16
+    }
+
+    /// Draws the help menu, which lists the keys bound to each action in the keymap. The menu
+    /// can be scrolled if it doesn't fit on the screen
+    fn draw_help(&mut self) -> Result<()> {
+        let mut lines = vec![
+            format!("Tasks v{}", env!("CARGO_PKG_VERSION")),
+            "Keybinds".to_string(),
+        ];
+        lines.extend(help_lines(self.keymap.actions(Mode::Normal)));
+        lines.push(String::new());
+        lines.push("While selecting tasks".to_string());
+        lines.extend(help_lines(self.keymap.actions(Mode::Visual)));
+        lines.push(String::new());
+        lines.push("Typing a number before a keybind repeats it, e.g. 5j".to_string());
+        lines.push("Typing \"a before a yank, delete or paste uses register a, \"+ is the system clipboard".to_string());
+
+        let (width, height) = terminal::size()?;
+        // Lines are cut short rather than wrapped, so that each one takes up a single row
+        let lines: Vec<String> = lines
+            .iter()
+            .map(|line| truncate(line, width as usize))
+            .collect();
+        let rows = height.saturating_sub(2).max(1) as usize;
+        let rows = rows.min(lines.len());
+        let mut offset = 0;
+
+        loop {
+            self.reserve_rows(rows as u16 + 2)?;
+            execute!(
+                stdout(),
+                self.move_to_origin(),
+                Clear(ClearType::FromCursorDown),
+                cursor::Hide
+            )?;
+            for line in &lines[offset..offset + rows] {
+                println(line)?;
+            }
+
+            // The cursor is left at the end of the last line, so the terminal doesn't scroll
+            let scrolls = rows < lines.len();
+            let footer = match scrolls {
+                true => "j/k to scroll, any other key to return".dimmed(),
+                false => "Press any key to return".normal(),
+            };
+            match self.full_screen {
+                true => execute!(
+                    stdout(),
+                    cursor::MoveTo(0, self.status_row()?),
+                    Print(footer)
+                )?,
+                false => execute!(stdout(), Print(format!("\n\r{}", footer)))?,
+            }
+
+            let key = match read()? {
+                Event::Key(key) => key,
+                _ => continue,
+            };
+            match key.code {
+                KeyCode::Char('j') | KeyCode::Down if scrolls => {
+                    offset = (offset + 1).min(lines.len() - rows)
+                }
+                KeyCode::Char('k') | KeyCode::Up if scrolls => offset = offset.saturating_sub(1),
+                _ => break,
+            }
+        }
+
+        Ok(())
+    }
+
+    // Moves the current_list_index to the next list
+    fn move_to_next_list(&mut self) {
+        if self.current_list_index + 1 < self.lists.len() {
+            self.current_list_index += 1;
+            // On the board the cursor stays on the same row as it moves across the columns
+            if self.board.is_none() {
+                self.current_task_index = 0;
+            }
+        }
+    }
+
+    // Moves the current_list_index to the previous list
+    fn move_to_prev_list(&mut self) {
+        self.current_list_index = self.current_list_index.saturating_sub(1);
+        if self.current_task_index >= self.lists[self.current_list_index].length() {
+            self.current_task_index = 0;
+        }
+    }
+
+    /// Moves to the given list if it exists
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the list to move to
+    fn move_to_list(&mut self, index: usize) {
+        if index < self.lists.len() {
+            self.current_list_index = index;
+        }
+    }
+
+    /// Swaps the current list with the one before it, keeping the cursor on the list
+    fn move_current_list_left(&mut self) {
+        if self.current_list_index > 0 {
+            self.swap_lists(self.current_list_index, self.current_list_index - 1);
+            self.current_list_index -= 1;
+        }
+    }
+
+    /// Swaps the current list with the one after it, keeping the cursor on the list
+    fn move_current_list_right(&mut self) {
+        if self.current_list_index + 1 < self.lists.len() {
+            self.swap_lists(self.current_list_index, self.current_list_index + 1);
+            self.current_list_index += 1;
+        }
+    }
+
+    /// Swaps the lists at the given indexes, making sure a filter on either of them stays on it
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The index of the first list
+    /// * `b` - The index of the second list
+    fn swap_lists(&mut self, a: usize, b: usize) {
+        self.lists.swap(a, b);
+        if let Some(Filter {
+            list: Some(index), ..
+        }) = &mut self.filter
+        {
+            if *index == a {
+                *index = b;
+            } else if *index == b {
+                *index = a;
+            }
+        }
+    }
+
+    /// Starts selecting tasks from the current one, or stops if tasks are already being selected
+    fn toggle_visual(&mut self) {
+        self.visual = match self.visual {
+            Some(_) => None,
+            None if self.current_index().is_some() => Some(self.current_task_index),
+            None => None,
+        };
+    }
+
+    /// Returns the first and last indexes into the shown tasks that are selected, if tasks are
+    /// being selected
+    fn selected_rows(&self) -> Option<RangeInclusive<usize>> {
+        let anchor = self.visual?;
+        Some(anchor.min(self.current_task_index)..=anchor.max(self.current_task_index))
+    }
+
+    /// Returns the indexes of the selected tasks in the current list, along with their subtasks
+    fn selected_indexes(&self) -> Vec<usize> {
+        let rows = match self.selected_rows() {
+            Some(rows) => rows,
+            None => return vec![],
+        };
+        let list = &self.lists[self.current_list_index];
+
+        let mut indexes: Vec<usize> = self
+            .visible_tasks()
+            .get(rows)
+            .unwrap_or_default()
+            .iter()
+            .flat_map(|index| *index..list.subtree_end(*index))
+            .collect();
+        indexes.sort_unstable();
+        indexes.dedup();
+        indexes
+    }
+
+    /// Copies the selected tasks into the register and stops selecting
+    fn yank_selection(&mut self) {
+        let tasks = self.lists[self.current_list_index].tasks_at(&self.selected_indexes());
+        self.yank_tasks(tasks, "yanked");
+        if let Some(rows) = self.selected_rows() {
+            self.current_task_index = *rows.start();
+        }
+        self.visual = None;
+    }
+
+    /// Deletes the selected tasks, putting them in the register
+    fn delete_selection(&mut self) {
+        let indexes = self.selected_indexes();
+        let tasks = self.lists[self.current_list_index].take_tasks(&indexes);
+        self.yank_tasks(tasks, "deleted");
+        if let Some(rows) = self.selected_rows() {
+            self.current_task_index = *rows.start();
+        }
+        self.visual = None;
+    }
+
+    /// Completes the selected tasks, or uncompletes them if they are all completed already
+    fn toggle_selection(&mut self) {
+        let rows = match self.selected_rows() {
+            Some(rows) => rows,
+            None => return,
+        };
+        let indexes = self.visible_tasks().get(rows).unwrap_or_default().to_vec();
+
+        let tasks = self.lists[self.current_list_index].tasks_at(&indexes);
+        let status = !tasks.iter().all(|task| task.status());
+        for (index, task) in indexes.into_iter().zip(tasks) {
+            if task.status() != status {
+                let result = self.lists[self.current_list_index].toggle_task(index);
+                self.report(result);
+            }
+        }
+        self.visual = None;
+    }
+
+    /// Moves the selected tasks to the end of the next or previous list, if there is one
+    ///
+    /// # Arguments
+    ///
+    /// * `next` - Whether to move the tasks to the next list rather than the previous one
+    fn move_selection_to_list(&mut self, next: bool) {
+        let target = match next {
+            true => self.current_list_index + 1,
+            false => match self.current_list_index.checked_sub(1) {
+                Some(index) => index,
+                None => return self.no_list_message(next),
+            },
+        };
+        if target >= self.lists.len() {
+            return self.no_list_message(next);
+        }
+
+        let indexes = self.selected_indexes();
+        let tasks = self.lists[self.current_list_index].take_tasks(&indexes);
+        self.visual = None;
+        self.current_list_index = target;
+        self.append_to_current_list(tasks);
+    }
+
+    /// Says that tasks can't be moved to the next or previous list, as there isn't one
+    ///
+    /// # Arguments
+    ///
+    /// * `next` - Whether the tasks were being moved to the next list rather than the previous one
+    fn no_list_message(&mut self, next: bool) {
+        let message = match next {
+            true => "There is no next list to move to",
+            false => "There is no previous list to move to",
+        };
+        self.message = Some(Message::Info(message.to_string()));
+    }
+
+    /// Replaces the selected tasks with the tasks in the register
+    fn paste_over_selection(&mut self) {
+        let indexes = self.selected_indexes();
+        let (index, depth) = match indexes.first() {
+            Some(index) => (
+                *index,
+                list_depth(&self.lists[self.current_list_index], *index),
+            ),
+            None => return,
+        };
+
+        let tasks = self.registers.get(self.register);
+        self.message = Some(Message::Info(tasks_message(tasks.len(), "pasted")));
+        let list = &mut self.lists[self.current_list_index];
+        list.take_tasks(&indexes);
+        list.insert_subtree(index, tasks, depth);
+        self.visual = None;
+        self.select_task(index);
+    }
+
+    /// Moves the task cursor down
+    fn move_to_next_task(&mut self) {
+        if self.current_task_index + 1 < self.visible_tasks().len() {
+            self.current_task_index += 1;
+        }
+    }
+
+    /// Moves the task cursor up
+    fn move_to_prev_task(&mut self) {
+        self.current_task_index = self.current_task_index.saturating_sub(1);
+    }
+
+    /// Returns the indexes of the tasks in the current list that are shown, in the order they
+    /// are shown. The current_task_index is an index into this
+    fn visible_tasks(&self) -> Vec<usize> {
+        self.visible_tasks_in(self.current_list_index)
+    }
+
+    /// Returns the indexes of the tasks in the given list that are shown, in the order they are
+    /// shown
+    ///
+    /// # Arguments
+    ///
+    /// * `list_index` - The index of the list
+    fn visible_tasks_in(&self, list_index: usize) -> Vec<usize> {
+        let list = &self.lists[list_index];
+        match self.filter_on(list_index) {
+            Some(filter) => list.filtered_indexes(|task| task.has_tag(&filter.tag)),
+            None => list.unfolded_indexes(),
+        }
+    }
+
+    /// Returns the filter applied to the current list, if there is one
+    fn active_filter(&self) -> Option<&Filter> {
+        self.filter_on(self.current_list_index)
+    }
+
+    /// Returns the filter applied to the given list, if there is one
+    ///
+    /// # Arguments
+    ///
+    /// * `list_index` - The index of the list
+    fn filter_on(&self, list_index: usize) -> Option<&Filter> {
+        self.filter
+            .as_ref()
+            .filter(|filter| filter.list.unwrap_or(list_index) == list_index)
+    }
+
+    /// Asks the user for text to search every list for, moving to the first task after the
+    /// current one that contains it as it is typed. Leaving the text empty stops highlighting the
+    /// last search, and cancelling returns to where the search started with the filter it had
+    fn search(&mut self) -> Result<()> {
+        let start = (
+            self.current_list_index,
+            self.current_task_index,
+            self.search.take(),
+            self.filter.clone(),
+        );
+        self.goto_empty_line()?;
+
+        // Reaching a match can clear the filter, so it's put back before every keystroke in case
+        // the match moves and whenever the search is cancelled
+        let pattern = typing_line_with("/", 1, String::new(), |pattern| {
+            self.current_list_index = start.0;
+            self.current_task_index = start.1;
+            self.filter = start.3.clone();
+            self.search = (!pattern.is_empty()).then(|| pattern.to_string());
+            self.message = None;
+            if self.search.is_some() {
+                self.goto_match(true);
+            }
+            self.clamp_cursor();
+
+            self.redraw()?;
+            self.goto_empty_line()
+        })?;
+
+        if pattern.is_none() {
+            self.current_list_index = start.0;
+            self.current_task_index = start.1;
+            self.search = start.2;
+            self.filter = start.3;
+            self.message = None;
+        }
+        Ok(())
+    }
+
+    /// Returns the list index and task index of every task that matches the current search, in
+    /// order
+    fn search_matches(&self) -> Vec<(usize, usize)> {
+        let pattern = match &self.search {
+            Some(pattern) => pattern,
+            None => return vec![],
+        };
+
+        self.lists
+            .iter()
+            .enumerate()
+            .flat_map(|(list_index, list)| {
+                list.tasks_iter()
+                    .enumerate()
+                    .filter(|(_, task)| task.find(pattern).is_some())
+                    .map(move |(task_index, _)| (list_index, task_index))
+            })
+            .collect()
+    }
+
+    /// Moves to the next or previous task that matches the current search, wrapping around at the
+    /// ends. Tasks folded away or hidden by a filter are shown
+    ///
+    /// # Arguments
+    ///
+    /// * `forward` - Whether to move to the next match rather than the previous one
+    fn goto_match(&mut self, forward: bool) {
+        let matches = self.search_matches();
+        if let (Some(pattern), true) = (&self.search, matches.is_empty()) {
+            self.message = Some(Message::Info(format!("No tasks match \"{}\"", pattern)));
+            return;
+        }
+        if self.search.is_none() {
+            self.message = Some(Message::Info("Nothing has been searched for".to_string()));
+            return;
+        }
+
+        let current = (self.current_list_index, self.current_index());
+
+        let target = match forward {
+            true => matches
+                .iter()
+                .find(|(list, task)| (*list, Some(*task)) > current)
+                .or(matches.first()),
+            false => matches
+                .iter()
+                .rev()
+                .find(|(list, task)| (*list, Some(*task)) < current)
+                .or(matches.last()),
+        };
+
+        if let Some(&(list, index)) = target {
+            self.current_list_index = list;
+            self.lists[list].unfold_parents(index);
+            if !self.visible_tasks().contains(&index) {
+                self.filter = None;
+            }
+            self.select_task(index);
+        }
+    }
+
+    /// Asks the user for a tag to filter tasks by. Leaving the tag empty clears the filter
+    ///
+    /// # Arguments
+    ///
+    /// * `all_lists` - Whether every list should be filtered, rather than just the current one
+    fn filter_by_tag(&mut self, all_lists: bool) -> Result<()> {
+        self.goto_empty_line()?;
+        let prompt = match all_lists {
+            true => "Filter all lists by tag: ",
+            false => "Filter list by tag: ",
+        };
+        let current_tag = self
+            .filter
+            .as_ref()
+            .map(|filter| filter.tag.clone())
+            .unwrap_or_default();
+
+        let tag = match typing_line(prompt, prompt.len() as u16, current_tag)? {
+            Some(tag) => tag.trim().to_string(),
+            None => return Ok(()),
+        };
+
+        let index = self.current_index();
+        self.filter = match tag.is_empty() {
+            true => None,
+            false => Some(Filter {
+                tag,
+                list: (!all_lists).then_some(self.current_list_index),
+            }),
+        };
+        if let Some(index) = index {
+            self.select_task(index);
+        }
+        Ok(())
+    }
+
+    /// Returns the index in the current list of the task that the current_task_index is
+    /// pointing to
+    fn current_index(&self) -> Option<usize> {
+        self.visible_tasks().get(self.current_task_index).copied()
+    }
+
+    /// Moves the task cursor to the task at the given index in the current list. If the task is
+    /// hidden in a folded task, then the cursor is moved to the folded task
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the task in the current list
+    fn select_task(&mut self, index: usize) {
+        self.current_task_index = self
+            .visible_tasks()
+            .iter()
+            .rposition(|visible| *visible <= index)
+            .unwrap_or(0);
+    }
+
+    /// Makes sure the current_task_index points to a task that is shown, if there are any
+    fn clamp_cursor(&mut self) {
+        self.current_list_index = self
+            .current_list_index
+            .min(self.lists.len().saturating_sub(1));
+        // Filters on a single list are cleared when moving away from it
+        if self.filter.is_some() && self.active_filter().is_none() {
+            self.filter = None;
+        }
+        self.current_task_index = self
+            .current_task_index
+            .min(self.visible_tasks().len().saturating_sub(1));
+    }
+
+    /// Gets the current task that the current_task_index is pointing to
+    fn get_current_task(&self) -> Option<Task> {
+        let index = self.current_index()?;
+        self.lists[self.current_list_index]
+            .tasks_iter()
+            .map(|x| x.to_owned())
+            .nth(index)
+    }
+
+    /// Toggles the status of the current task. If the task is completed and it has subtasks that
+    /// are not, then the user is asked whether they should be completed too
+    fn toggle_current_task(&mut self) -> Result<()> {
+        let index = match self.current_index() {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+
+        let result = self.lists[self.current_list_index].toggle_task(index);
+        if self.report(result).is_none() {
+            return Ok(());
+        }
+
+        let incomplete = self.lists[self.current_list_index].incomplete_subtask_count(index);
+        if !self.get_current_task().is_some_and(|task| task.status()) || incomplete == 0 {
+            return Ok(());
+        }
+
+        self.goto_empty_line()?;
+        execute!(
+            stdout(),
+            Print(format!(
+                "[{}] Also complete {} subtask{}? y/N ",
+                "?".bright_blue(),
+                incomplete,
+                if incomplete == 1 { "" } else { "s" }
+            ))
+        )?;
+
+        if get_confirmation()? {
+            self.lists[self.current_list_index].set_subtasks_status(index, true);
+        }
+        Ok(())
+    }
+
+    /// Makes the current task a subtask of the task above it
+    fn indent_current_task(&mut self) {
+        if let Some(index) = self.current_index() {
+            let list = &mut self.lists[self.current_list_index];
+            let depth = list_depth(list, index);
+            list.indent_task(index);
+            if list_depth(list, index) == depth {
+                let message = "There is no task above this one to make it a subtask of";
+                self.message = Some(Message::Info(message.to_string()));
+            }
+        }
+    }
+
+    /// Moves the current task out of the task it is nested in
+    fn outdent_current_task(&mut self) {
+        if let Some(index) = self.current_index() {
+            let list = &mut self.lists[self.current_list_index];
+            if list_depth(list, index) == 0 {
+                self.message = Some(Message::Info("This task isn't a subtask".to_string()));
+            }
+            list.outdent_task(index);
+        }
+    }
+
+    /// Folds or unfolds the subtasks of the current task
+    fn toggle_current_fold(&mut self) {
+        if let Some(index) = self.current_index() {
+            let list = &mut self.lists[self.current_list_index];
+            if list.subtask_count(index) == 0 {
+                self.message = Some(Message::Info(
+                    "This task has no subtasks to fold".to_string(),
+                ));
+            }
+            list.toggle_fold(index);
+        }
+    }
+
+    /// Folds or unfolds every task in the current list
+    ///
+    /// # Arguments
+    ///
+    /// * `folded` - Whether the tasks should be folded
+    fn set_current_list_folded(&mut self, folded: bool) {
+        let index = self.current_index();
+        self.lists[self.current_list_index].set_all_folded(folded);
+        if let Some(index) = index {
+            self.select_task(index);
+        }
+    }
+
+    /// Moves the current task to the next list, if there is one
+    fn move_current_task_to_next_list(&mut self) {
+        if self.current_list_index + 1 >= self.lists.len() {
+            return self.no_list_message(true);
+        }
+
+        let index = match self.current_index() {
+            Some(index) => index,
+            None => return,
+        };
+
+        let tasks = self.lists[self.current_list_index].take_subtree(index);
+        self.move_to_next_list();
+        self.append_to_current_list(tasks);
+    }
+
+    /// Moves the current task to the list previous to the current one, if there is one
+    fn move_current_task_to_prev_list(&mut self) {
+        if self.current_list_index == 0 {
+            return self.no_list_message(false);
+        }
+
+        let index = match self.current_index() {
+            Some(index) => index,
+            None => return,
+        };
+
+        let tasks = self.lists[self.current_list_index].take_subtree(index);
+        self.move_to_prev_list();
+        self.append_to_current_list(tasks);
+    }
+
+    /// Swaps the current task with the one below it, keeping the cursor on the task
+    fn move_current_task_down(&mut self) {
+        if let Some(index) = self.current_index() {
+            if let Some(index) = self.lists[self.current_list_index].move_task_down(index) {
+                self.select_task(index);
+            }
+        }
+    }
+
+    /// Swaps the current task with the one above it, keeping the cursor on the task
+    fn move_current_task_up(&mut self) {
+        if let Some(index) = self.current_index() {
+            if let Some(index) = self.lists[self.current_list_index].move_task_up(index) {
+                self.select_task(index);
+            }
+        }
+    }
+
+    /// Adds the given tasks to the end of the current list as top level tasks, and moves the
+    /// cursor to the first of them
+    ///
+    /// # Arguments
+    ///
+    /// * `tasks` - The tasks to add
+    fn append_to_current_list(&mut self, tasks: Vec<Task>) {
+        let moved = format!("moved to {}", self.lists[self.current_list_index].name());
+        self.message = Some(Message::Info(tasks_message(tasks.len(), &moved)));
+        let list = &mut self.lists[self.current_list_index];
+        let index = list.length();
+        list.insert_subtree(index, tasks, 0);
+        self.select_task(index);
+    }
+
+    /// Creates a new list
+    fn create_new_list(&mut self) -> Result<()> {
+        execute!(
+            stdout(),
+            self.move_to_origin(),
+            Clear(ClearType::FromCursorDown)
+        )?;
+        let prompt = format!(
+            "({}/{}) ",
+            self.current_list_index + 2,
+            self.lists.len() + 1
+        );
+
+        let prompt_length = prompt.len() as u16;
+
+        let name = typing_line(prompt, prompt_length, String::new())?;
+
+        if let Some(name) = name {
+            if let Some(mut list) = self.report(List::new(name)) {
+                // New lists are written the same way as the list they are created after
+                list.set_heading(self.lists[self.current_list_index].heading());
+                self.lists.insert(self.current_list_index + 1, list);
+                self.current_list_index += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Renames the current list
+    fn rename_current_list(&mut self) -> Result<()> {
+        execute!(stdout(), self.move_to_origin())?;
+
+        let prompt = format!("({}/{}) ", self.current_list_index + 1, self.lists.len());
+        let prompt_length = prompt.len() as u16;
+        let new_name = typing_line(
+            prompt,
+            prompt_length,
             self.lists[self.current_list_index].name(),
         )?;
-
-        if let Some(new_name) = new_name {
-            self.lists[self.current_list_index].rename_list(new_name);
+
+        if let Some(new_name) = new_name {
+            let result = self.lists[self.current_list_index].rename_list(new_name);
+            self.report(result);
+        }
+        Ok(())
+    }
+
+    /// Deletes the current list
+    fn delete_current_list(&mut self) -> Result<()> {
+        if self.lists.len() == 1 {
+            let message = "At least one list has to be kept".to_string();
+            self.message = Some(Message::Error(message));
+            return Ok(());
+        }
+
+        self.goto_empty_line()?;
+        let message = format!(
+            "[{}] This will delete this list, are you sure? y/N",
+            "!".bright_red()
+        );
+        execute!(stdout(), Print(message))?;
+
+        if !get_confirmation()? {
+            return Ok(());
+        }
+
+        let list = self.lists.remove(self.current_list_index);
+        self.current_list_index = self.current_list_index.saturating_sub(1);
+        self.message = Some(Message::Info(format!("Deleted the list {}", list.name())));
+        Ok(())
+    }
+
+    /// Shows every list along with how many of its tasks are completed, so that lists can be
+    /// reordered, renamed and deleted in one place. Leaving the manager opens the selected list
+    fn manage_lists(&mut self) -> Result<()> {
+        let mut selected = self.current_list_index;
+        let mut marked = vec![false; self.lists.len()];
+
+        loop {
+            self.draw_list_manager(&marked)?;
+            let prefix_len = list_manager_prefix(false, selected).len() as u16;
+            execute!(
+                stdout(),
+                cursor::MoveTo(prefix_len, self.origin_row + 1 + selected as u16)
+            )?;
+
+            let key = match read()? {
+                Event::Key(key) => key,
+                _ => continue,
+            };
+            self.message = None;
+            match key.code {
+                KeyCode::Char('j') | KeyCode::Down if selected + 1 < self.lists.len() => {
+                    selected += 1
+                }
+                KeyCode::Char('k') | KeyCode::Up => selected = selected.saturating_sub(1),
+                KeyCode::Char('J') if selected + 1 < self.lists.len() => {
+                    self.swap_lists(selected, selected + 1);
+                    marked.swap(selected, selected + 1);
+                    selected += 1;
+                }
+                KeyCode::Char('K') if selected > 0 => {
+                    self.swap_lists(selected, selected - 1);
+                    marked.swap(selected, selected - 1);
+                    selected -= 1;
+                }
+                KeyCode::Char('r') => {
+                    let prompt = list_manager_prefix(marked[selected], selected);
+                    let name = typing_line(prompt, prefix_len, self.lists[selected].name())?;
+                    if let Some(name) = name {
+                        let result = self.lists[selected].rename_list(name);
+                        self.report(result);
+                    }
+                }
+                KeyCode::Char(' ') | KeyCode::Char('x') => marked[selected] = !marked[selected],
+                KeyCode::Char('d') => {
+                    self.delete_marked_lists(&mut marked)?;
+                    selected = selected.min(self.lists.len() - 1);
+                }
+                KeyCode::Enter | KeyCode::Char('q') | KeyCode::Esc => break,
+                _ => (),
+            }
+        }
+
+        self.current_list_index = selected;
+        self.current_task_index = 0;
+        Ok(())
+    }
+
+    /// Draws the list manager
+    ///
+    /// # Arguments
+    ///
+    /// * `marked` - Whether each list is marked to be deleted
+    fn draw_list_manager(&mut self, marked: &[bool]) -> Result<()> {
+        self.reserve_rows(self.lists.len() as u16 + 3)?;
+        execute!(
+            stdout(),
+            self.move_to_origin(),
+            Clear(ClearType::FromCursorDown)
+        )?;
+
+        println("Lists")?;
+        for (i, list) in self.lists.iter().enumerate() {
+            let prefix = match marked[i] {
+                true => list_manager_prefix(true, i).bright_red().to_string(),
+                false => list_manager_prefix(false, i),
+            };
+            let count = format!("{}/{}", list.completed_count(), list.length());
+            println(format!("{}{} {}", prefix, list.name(), count.dimmed()))?;
+        }
+        match &self.message {
+            Some(message) => println(message)?,
+            None => println(
+                "j/k select, J/K move, r rename, x mark, d delete marked, q back"
+                    .to_string()
+                    .dimmed(),
+            )?,
+        }
+        Ok(())
+    }
+
+    /// Deletes the lists that are marked after asking the user to confirm. At least one list is
+    /// always kept
+    ///
+    /// # Arguments
+    ///
+    /// * `marked` - Whether each list is marked to be deleted, updated to match the lists left
+    fn delete_marked_lists(&mut self, marked: &mut Vec<bool>) -> Result<()> {
+        let count = marked.iter().filter(|marked| **marked).count();
+        if count == 0 {
+            return Ok(());
+        }
+
+        execute!(
+            stdout(),
+            cursor::MoveTo(0, self.origin_row + 2 + self.lists.len() as u16)
+        )?;
+        if count == self.lists.len() {
+            let message = format!(
+                "[{}] At least one list has to be kept, press any key",
+                "!".bright_red()
+            );
+            execute!(stdout(), Print(message))?;
+            read()?;
+            return Ok(());
         }
-        Ok(())
-    }
 
-    /// Deletes the current list
-    fn delete_current_list(&mut self) -> Result<()> {
-        self.goto_empty_line()?;
         let message = format!(
-            "[{}] This will delete this list, are you sure? y/N",
-            "!".bright_red()
+            "[{}] This will delete {} list{}, are you sure? y/N",
+            "!".bright_red(),
+            count,
+            if count == 1 { "" } else { "s" }
         );
         execute!(stdout(), Print(message))?;
-
         if !get_confirmation()? {
             return Ok(());
         }
 
-        if self.lists.len() > 1 {
-            self.lists.remove(self.current_list_index);
-            self.current_list_index = self.current_list_index.saturating_sub(1);
+        let mut index = 0;
+        self.lists.retain(|_| {
+            index += 1;
+            !marked[index - 1]
+        });
+        marked.retain(|marked| !marked);
+        let message = format!(
+            "Deleted {} list{}",
+            count,
+            if count == 1 { "" } else { "s" }
+        );
+        self.message = Some(Message::Info(message));
+        // The indexes of the lists have changed, so a filter on one of them no longer applies
+        if matches!(self.filter, Some(Filter { list: Some(_), .. })) {
+            self.filter = None;
         }
-
         Ok(())
     }
 
@@ -407,134 +1973,543 @@ impl TasksApp {
         let description = typing_line("[ ] ", 4, String::new())?;
 
         if let Some(description) = description {
-            self.lists[self.current_list_index].add_task(description);
+            let result = self.lists[self.current_list_index].add_task(description);
+            self.report(result);
         }
         Ok(())
     }
 
     /// Rewords the current task
     fn reword_current_task(&mut self) -> Result<()> {
-        if self.current_task_index >= self.lists[self.current_list_index].length() {
-            return Ok(());
-        }
-
-        execute!(
-            stdout(),
-            RestorePosition,
-            cursor::MoveDown((self.current_task_index + 1) as u16),
-        )?;
+        let (index, task) = match (self.current_index(), self.get_current_task()) {
+            (Some(index), Some(task)) => (index, task),
+            _ => return Ok(()),
+        };
 
-        let task = self.lists[self.current_list_index]
-            .tasks_iter()
-            .nth(self.current_task_index)
-            .expect("We know this task exists so this can't fail");
+        // Tasks on the board are reworded on the line below it, as they may be wrapped
+        match self.board {
+            Some(_) => self.goto_empty_line()?,
+            None => execute!(
+                stdout(),
+                cursor::MoveTo(0, self.task_row(self.current_task_index))
+            )?,
+        }
 
+        let indent = "  ".repeat(task.depth());
+        let checkbox = task.checkbox();
+        let checkbox_len = match task.status() {
+            true => 3 + theme().checkmark.width(),
+            false => 4,
+        };
         let description = typing_line(
-            match task.status() {
-                true => format!("[{}] ", "✔".bright_green()),
-                false => "[ ] ".to_string(),
-            },
-            4,
-            task.description(),
+            format!("{}{}", indent, checkbox),
+            (checkbox_len + indent.len()) as u16,
+            task.text(),
         )?;
 
         if let Some(description) = description {
-            self.lists[self.current_list_index].reword_task(self.current_task_index, description);
+            let result = self.lists[self.current_list_index].reword_task(index, description);
+            self.report(result);
         }
         Ok(())
     }
 
+    /// Asks the user for the date the current task is due. Leaving the date empty clears it
+    fn set_current_task_due(&mut self) -> Result<()> {
+        let (index, task) = match (self.current_index(), self.get_current_task()) {
+            (Some(index), Some(task)) => (index, task),
+            _ => return Ok(()),
+        };
+
+        let mut prompt = "Due: ";
+        let mut input = task.due().map(|due| due.to_string()).unwrap_or_default();
+
+        loop {
+            self.goto_empty_line()?;
+            input = match typing_line(prompt, prompt.len() as u16, input)? {
+                Some(input) => input,
+                None => return Ok(()),
+            };
+
+            if input.trim().is_empty() {
+                let result = self.lists[self.current_list_index].set_task_due(index, None);
+                self.report(result);
+                return Ok(());
+            }
+
+            if let Some(due) = parse_date_input(&input, today()) {
+                let result = self.lists[self.current_list_index].set_task_due(index, Some(due));
+                self.report(result);
+                return Ok(());
+            }
+
+            prompt = "Invalid date, due: ";
+        }
+    }
+
+    /// Makes the current task more important
+    fn raise_current_task_priority(&mut self) {
+        if let (Some(index), Some(task)) = (self.current_index(), self.get_current_task()) {
+            let priority = match task.priority() {
+                Some(priority) => priority.raised().or(Some(priority)),
+                None => Some(Priority::C),
+            };
+            let result = self.lists[self.current_list_index].set_task_priority(index, priority);
+            self.report(result);
+        }
+    }
+
+    /// Makes the current task less important, clearing its priority if it is already the least
+    /// important
+    fn lower_current_task_priority(&mut self) {
+        if let (Some(index), Some(task)) = (self.current_index(), self.get_current_task()) {
+            let priority = task.priority().and_then(|priority| priority.lowered());
+            let result = self.lists[self.current_list_index].set_task_priority(index, priority);
+            self.report(result);
+        }
+    }
+
+    /// Shows the ways the list can be sorted and waits for the user to pick one
+    ///
+    /// # Returns
+    ///
+    /// The key to sort by, or None if the key pressed doesn't match one
+    fn read_sort_key(&mut self) -> Result<Option<SortKey>> {
+        self.goto_empty_line()?;
+        execute!(
+            stdout(),
+            Print("Sort by: (s)tatus, (p)riority, (d)ue date, (a)lphabetical, (i)nsertion order")
+        )?;
+
+        if let Event::Key(key) = read()? {
+            return Ok(match key.code {
+                KeyCode::Char('s') => Some(SortKey::Status),
+                KeyCode::Char('p') => Some(SortKey::Priority),
+                KeyCode::Char('d') => Some(SortKey::DueDate),
+                KeyCode::Char('a') => Some(SortKey::Alphabetical),
+                KeyCode::Char('i') => Some(SortKey::Insertion),
+                _ => None,
+            });
+        }
+        Ok(None)
+    }
+
     /// Sorts the current list
-    fn sort_current_list(&mut self) {
-        self.lists[self.current_list_index].sort_list();
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - What to sort the tasks by
+    fn sort_current_list(&mut self, key: SortKey) {
+        self.lists[self.current_list_index].sort_list(key);
     }
 
     /// Sorts all the lists in the app
-    fn sort_all_lists(&mut self) {
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - What to sort the tasks by
+    fn sort_all_lists(&mut self, key: SortKey) {
         self.lists = self
             .lists
             .iter_mut()
             .map(|list| {
-                list.sort_list();
+                list.sort_list(key);
                 list.to_owned()
             })
             .collect();
     }
 
-    /// Pastes the task in the clipboard, if it is not None, into the current list
+    /// Pastes the tasks in the register into the current list
     ///
     /// # Arguments
     ///
     /// * `offset` - The offset of where to paste the task from the current_task_index
-    fn paste_clipboard(&mut self, offset: usize) {
-        for task in &self.clipboard {
-            self.lists[self.current_list_index]
-                .insert_task(self.current_task_index + offset, task.clone());
-            // If the task is being pasted as the first task in a list, then moving the current
-            // task index results in a UI glitch
-            if self.lists[self.current_list_index].length() > 1 {
-                self.current_task_index += offset;
-            }
+    /// * `times`  - How many copies of the tasks to paste
+    fn paste_clipboard(&mut self, offset: usize, times: usize) {
+        let list = &self.lists[self.current_list_index];
+        let (index, depth) = match self.current_index() {
+            // Pasting below a task goes after its subtasks, so they stay with it
+            Some(index) if offset > 0 => (list.subtree_end(index), list_depth(list, index)),
+            Some(index) => (index, list_depth(list, index)),
+            None => (0, 0),
+        };
+
+        let register = self.registers.get(self.register);
+        if register.is_empty() {
+            self.message = Some(Message::Info("There is nothing to paste".to_string()));
+            return;
         }
+
+        let tasks: Vec<Task> = (0..times).flat_map(|_| register.clone()).collect();
+        self.message = Some(Message::Info(tasks_message(tasks.len(), "pasted")));
+        self.lists[self.current_list_index].insert_subtree(index, tasks, depth);
+        self.select_task(index);
     }
 
-    /// Copies the current task into the clipboard
+    /// Copies the current task, along with its subtasks, into the register
     fn yank_current_task(&mut self) {
-        if let Some(current_task) = self.get_current_task() {
-            // Clearing the list, as yanking only gets the current task
-            self.clipboard = vec![];
-            self.clipboard.push(current_task);
+        if let Some(index) = self.current_index() {
+            // Replacing the register, as yanking only gets the current task
+            let tasks = self.lists[self.current_list_index].subtree(index);
+            self.yank_tasks(tasks, "yanked");
         }
     }
 
-    /// Copies all the tasks in the current list into the clipboard
+    /// Copies all the tasks in the current list into the register
     fn yank_current_list(&mut self) {
-        self.clipboard = self.lists[self.current_list_index]
+        let tasks = self.lists[self.current_list_index]
             .tasks_iter()
             .map(|x| x.to_owned())
             .collect();
+        self.yank_tasks(tasks, "yanked");
+    }
+
+    /// Copies the given tasks into the register, saying how many were yanked or deleted. If they
+    /// couldn't be copied to the system clipboard that is reported instead
+    ///
+    /// # Arguments
+    ///
+    /// * `tasks` - The tasks to copy
+    /// * `done`  - What was done to the tasks, used in the message
+    fn yank_tasks(&mut self, tasks: Vec<Task>, done: &str) {
+        let done = match self.register {
+            Some(register) => format!("{} into \"{}", done, register),
+            None => done.to_string(),
+        };
+        self.message = Some(Message::Info(tasks_message(tasks.len(), &done)));
+        let result = self.registers.yank(self.register, tasks);
+        self.report(result);
     }
 
     /// Deletes the current task
     fn delete_current_task(&mut self) {
-        self.yank_current_task();
-        self.lists[self.current_list_index].delete_task(self.current_task_index);
+        let index = match self.current_index() {
+            Some(index) => index,
+            None => return,
+        };
+
+        let tasks = self.lists[self.current_list_index].subtree(index);
+        self.yank_tasks(tasks, "deleted");
+        let result = self.lists[self.current_list_index].delete_task(index);
+        self.report(result);
         self.current_task_index = self.current_task_index.saturating_sub(1);
     }
 
     /// Removes all completed tasks from the current list
     fn delete_completed_tasks(&mut self) {
-        self.lists[self.current_list_index].delete_completed_tasks();
+        let list = &mut self.lists[self.current_list_index];
+        let length = list.length();
+        list.delete_completed_tasks();
+        let deleted = length - list.length();
+        self.completed_deleted_message(deleted);
         self.current_task_index = 0;
     }
 
     /// Removes all tasks from the current list
     fn delete_all_tasks(&mut self) {
-        self.yank_current_list();
+        let tasks: Vec<Task> = self.lists[self.current_list_index]
+            .tasks_iter()
+            .map(|x| x.to_owned())
+            .collect();
+        if tasks.is_empty() {
+            self.message = Some(Message::Info("This list has no tasks".to_string()));
+            return;
+        }
+
+        self.yank_tasks(tasks, "deleted");
         self.lists[self.current_list_index].delete_all_tasks();
         self.current_task_index = 0;
     }
 
     /// Deletes all completed tasks on every list in the app
     fn delete_completed_tasks_on_all_lists(&mut self) {
+        let mut deleted = 0;
         for list in &mut self.lists {
+            let length = list.length();
             list.delete_completed_tasks();
+            deleted += length - list.length();
         }
+        self.completed_deleted_message(deleted);
         self.current_task_index = 0;
     }
 
-    /// Moves the cursor to the next empty line
+    /// Says how many completed tasks were deleted
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The number of tasks deleted
+    fn completed_deleted_message(&mut self, count: usize) {
+        let message = match count {
+            0 => "There are no completed tasks to delete".to_string(),
+            _ => tasks_message(count, "deleted"),
+        };
+        self.message = Some(Message::Info(message));
+    }
+
+    /// Moves the cursor to the start of the status line and clears it, so that a prompt or
+    /// message can be shown in its place
     fn goto_empty_line(&mut self) -> Result<()> {
         execute!(
             stdout(),
-            RestorePosition,
-            cursor::MoveDown((self.lists[self.current_list_index].length() + 1) as u16),
-        )?;
-        Ok(())
+            cursor::MoveTo(0, self.status_row()?),
+            Clear(ClearType::CurrentLine)
+        )
+    }
+
+    /// Returns the number of rows the tasks are drawn in, not including the title
+    fn app_height(&self) -> usize {
+        match &self.board {
+            Some(board) => board.height(),
+            None => self.viewport.height(),
+        }
+    }
+
+    /// Moves the task cursor to the given row of the shown tasks, or the last one if there are
+    /// fewer rows
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The index of the row in the shown tasks
+    fn goto_row(&mut self, row: usize) {
+        self.current_task_index = row.min(self.visible_tasks().len().saturating_sub(1));
+    }
+
+    /// Moves the task cursor to the top, middle or bottom of the tasks drawn on the screen
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Which of the tasks drawn to move to
+    fn goto_screen_row(&mut self, position: ScreenRow) {
+        // The board scrolls each column on its own, so the whole list is used instead
+        let rows = match self.board {
+            Some(_) => 0..self.visible_tasks().len(),
+            None => self.viewport.visible(),
+        };
+        if rows.is_empty() {
+            return;
+        }
+
+        let row = match position {
+            ScreenRow::Top => rows.start,
+            ScreenRow::Middle => rows.start + (rows.len() - 1) / 2,
+            ScreenRow::Bottom => rows.end - 1,
+        };
+        self.goto_row(row);
+    }
+
+    /// Selects the given number of tasks starting from the current one, as if they had been
+    /// selected in visual mode
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The number of tasks to select
+    fn select_rows(&mut self, count: usize) {
+        if self.current_index().is_none() {
+            return;
+        }
+
+        self.visual = Some(self.current_task_index);
+        self.goto_row(self.current_task_index + count.saturating_sub(1));
     }
 
     fn goto_bottom(&mut self) {
-        self.current_task_index = self.lists[self.current_list_index].length() - 1;
+        self.current_task_index = self.visible_tasks().len().saturating_sub(1);
+    }
+}
+
+/// Returns the depth of the task at the given index in a list
+///
+/// # Arguments
+///
+/// * `list`  - The list the task is in
+/// * `index` - The index of the task
+fn list_depth(list: &List, index: usize) -> usize {
+    list.tasks_iter()
+        .nth(index)
+        .map(|task| task.depth())
+        .unwrap_or_default()
+}
+
+/// Formats the actions in a keymap as lines of the help menu
+///
+/// # Arguments
+///
+/// * `actions` - The actions along with the keys bound to them
+fn help_lines(actions: Vec<(Action, Vec<String>)>) -> Vec<String> {
+    let keys: Vec<String> = actions.iter().map(|(_, keys)| keys.join(" ")).collect();
+    let width = keys
+        .iter()
+        .map(|keys| keys.chars().count())
+        .max()
+        .unwrap_or(0)
+        + 2;
+
+    actions
+        .iter()
+        .zip(keys)
+        .map(|((action, _), keys)| format!("{:<width$}{}", keys, action.description()))
+        .collect()
+}
+
+/// Formats the start of a row in the list manager, before the name of the list
+///
+/// # Arguments
+///
+/// * `marked` - Whether the list is marked to be deleted
+/// * `index`  - The index of the list
+fn list_manager_prefix(marked: bool, index: usize) -> String {
+    let mark = match marked {
+        true => 'x',
+        false => ' ',
+    };
+    format!("{} {:>2}. ", mark, index + 1)
+}
+
+/// Pads styled text with spaces so that it takes up the given width
+///
+/// # Arguments
+///
+/// * `text`  - The text to pad
+/// * `width` - The width to pad the text to
+fn pad(text: &str, width: usize) -> String {
+    let padding = width.saturating_sub(visible_width(text));
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+/// Formats the tabs drawn along the top of the terminal for each list, with the current one in
+/// reverse video. If they don't all fit, the tabs before the current one are left out first
+///
+/// # Arguments
+///
+/// * `lists`   - The lists to draw tabs for
+/// * `current` - The index of the current list
+/// * `width`   - The width of the terminal
+fn list_tabs(lists: &[List], current: usize, width: u16) -> String {
+    let width = width as usize;
+    let tabs: Vec<String> = lists
+        .iter()
+        .enumerate()
+        .map(|(i, list)| format!(" {}. {} ", i + 1, list.name()))
+        .collect();
+
+    let mut start = 0;
+    while start < current
+        && tabs[start..=current]
+            .iter()
+            .map(|tab| tab.width())
+            .sum::<usize>()
+            > width
+    {
+        start += 1;
+    }
+
+    let mut line = String::new();
+    let mut used = 0;
+    for (i, tab) in tabs.iter().enumerate().skip(start) {
+        if used >= width {
+            break;
+        }
+        let tab = truncate(tab, width - used);
+        used += tab.width();
+        match i == current {
+            true => line.push_str(&tab.bold().reversed().to_string()),
+            false => line.push_str(&tab),
+        }
+    }
+    line
+}
+
+/// Shortens styled text so that it fits on a single row, ending it with an ellipsis if any of it
+/// was cut off
+///
+/// # Arguments
+///
+/// * `text`  - The text to shorten
+/// * `width` - The most columns the text can take up
+fn cut_short(text: &str, width: usize) -> String {
+    if visible_width(text) <= width {
+        return text.to_string();
+    }
+    let first_row = wrap(text, width.saturating_sub(1).max(1), 0).swap_remove(0);
+    format!("{}…", first_row)
+}
+
+/// Formats a message saying how many tasks an action was carried out on, e.g. `3 tasks deleted`
+///
+/// # Arguments
+///
+/// * `count` - The number of tasks
+/// * `done`  - What was done to the tasks
+fn tasks_message(count: usize, done: &str) -> String {
+    format!(
+        "{} task{} {}",
+        count,
+        if count == 1 { "" } else { "s" },
+        done
+    )
+}
+
+/// Formats a bar that is filled in as far as the given tasks are completed
+///
+/// # Arguments
+///
+/// * `completed` - The number of completed tasks
+/// * `total`     - The number of tasks
+/// * `width`     - How many columns the bar takes up
+fn progress_bar(completed: usize, total: usize, width: usize) -> String {
+    let filled = match total {
+        0 => 0,
+        _ => completed * width / total,
+    };
+    format!(
+        "{}{}",
+        "█".repeat(filled).color(theme().checkmark_colour),
+        "░".repeat(width - filled).dimmed()
+    )
+}
+
+/// Describes the tasks that would be pasted, either the task itself if there is one or how many
+/// there are
+///
+/// # Arguments
+///
+/// * `tasks` - The tasks yanked or deleted last
+///
+/// # Returns
+///
+/// The description, or None if there are no tasks to paste
+fn clipboard_summary(tasks: &[Task]) -> Option<String> {
+    match tasks {
+        [] => None,
+        [task] => Some(format!("Clipboard: \"{}\"", truncate(&task.text(), 20))),
+        tasks => Some(format!("Clipboard: {} tasks", tasks.len())),
+    }
+}
+
+/// Returns how many columns the status line takes up with the given parts
+///
+/// # Arguments
+///
+/// * `parts` - The parts of the status line, each paired with how important it is
+fn status_width(parts: &[(usize, String)]) -> usize {
+    let separators = STATUS_SEPARATOR.len() * parts.len().saturating_sub(1);
+    parts
+        .iter()
+        .map(|(_, part)| visible_width(part))
+        .sum::<usize>()
+        + separators
+}
+
+/// Formats the message shown when tasks are hidden above or below the viewport
+///
+/// # Arguments
+///
+/// * `count`     - The number of hidden tasks
+/// * `direction` - Where the tasks are hidden, either "above" or "below"
+fn hidden_tasks_message(count: usize, direction: &str) -> String {
+    match count {
+        0 => String::new(),
+        _ => format!("    {}", format!("{} more {}", count, direction).dimmed()),
     }
 }
 
@@ -551,45 +2526,239 @@ impl TasksApp {
 /// If no errors occured, an Option containing None if the user canceled the operation, or Some
 /// containing what the user inputted
 fn typing_line<T: ToString>(prompt: T, prompt_len: u16, content: String) -> Result<Option<String>> {
+    typing_line_with(prompt, prompt_len, content, |_| Ok(()))
+}
+
+/// The same as `typing_line`, but calls `on_change` with the text every time it changes. The
+/// callback must leave the terminal cursor on the line the textbox is on
+///
+/// # Arguments
+///
+/// * `prompt` - What the textbox prompt should be
+/// * `prompt_len` - The length of the prompt
+/// * `content` - The initial content of the textfield
+/// * `on_change` - Called with the text after every change
+fn typing_line_with<T, F>(
+    prompt: T,
+    prompt_len: u16,
+    content: String,
+    mut on_change: F,
+) -> Result<Option<String>>
+where
+    T: ToString,
+    F: FnMut(&str) -> Result<()>,
+{
     execute!(stdout(), cursor::Show, cursor::SetCursorStyle::SteadyBlock)?;
 
     let mut output = content;
     let prompt = prompt.to_string();
 
+    // The byte index of the cursor in the output, which is always between two graphemes
     let mut cursor = output.len();
 
     loop {
+        let column = prompt_len + output[..cursor].width() as u16;
         execute!(
             stdout(),
             Clear(ClearType::CurrentLine),
             Print(format!("\r{}{}", prompt, &output)),
-            cursor::MoveToColumn(0),
-            cursor::MoveRight(prompt_len + (cursor as u16))
+            cursor::MoveToColumn(column)
         )?;
         if let Event::Key(key) = read()? {
             match key.code {
-                KeyCode::Char(char) => {
-                    output.insert(cursor, char);
-                    cursor += 1;
-                }
-                KeyCode::Backspace => {
-                    if !output.is_empty() {
-                        output.remove(cursor - 1);
-                        cursor = cursor.saturating_sub(1);
-                    }
-                }
                 KeyCode::Esc => return Ok(None),
                 KeyCode::Enter => break,
-                KeyCode::Left => cursor = cursor.saturating_sub(1),
-                KeyCode::Right => {
-                    if cursor != output.len() {
-                        cursor += 1;
+                code => {
+                    if edit_text(&mut output, &mut cursor, code) {
+                        on_change(&output)?;
+                        execute!(stdout(), cursor::SetCursorStyle::SteadyBlock)?;
                     }
                 }
-                _ => (),
             }
         }
     }
 
     Ok(Some(output))
 }
+
+/// Carries out a key pressed in a textbox, typing a character, deleting the grapheme before the
+/// cursor or moving the cursor
+///
+/// # Arguments
+///
+/// * `text`   - The text in the textbox
+/// * `cursor` - The byte index of the cursor in the text, which is always between two graphemes
+/// * `code`   - The key that was pressed
+///
+/// # Returns
+///
+/// Whether the text was changed
+fn edit_text(text: &mut String, cursor: &mut usize, code: KeyCode) -> bool {
+    match code {
+        KeyCode::Char(char) => {
+            text.insert(*cursor, char);
+            *cursor += char.len_utf8();
+            true
+        }
+        KeyCode::Backspace if *cursor > 0 => {
+            let previous = previous_grapheme(text, *cursor);
+            text.replace_range(previous..*cursor, "");
+            *cursor = previous;
+            true
+        }
+        KeyCode::Left => {
+            *cursor = previous_grapheme(text, *cursor);
+            false
+        }
+        KeyCode::Right => {
+            *cursor = next_grapheme(text, *cursor);
+            false
+        }
+        _ => false,
+    }
+}
+
+/// Returns the byte index of the start of the grapheme before the given index, so that accented
+/// letters and emoji are moved over and deleted as a whole
+///
+/// # Arguments
+///
+/// * `text`  - The text to look in
+/// * `index` - A byte index in the text that is at the start of a grapheme
+fn previous_grapheme(text: &str, index: usize) -> usize {
+    text[..index]
+        .grapheme_indices(true)
+        .next_back()
+        .map_or(0, |(start, _)| start)
+}
+
+/// Returns the byte index of the start of the grapheme after the one at the given index
+///
+/// # Arguments
+///
+/// * `text`  - The text to look in
+/// * `index` - A byte index in the text that is at the start of a grapheme
+fn next_grapheme(text: &str, index: usize) -> usize {
+    text[index..]
+        .graphemes(true)
+        .next()
+        .map_or(index, |grapheme| index + grapheme.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::serialisation::Document;
+
+    /// Creates an app showing lists with the given names and numbers of tasks
+    ///
+    /// # Arguments
+    ///
+    /// * `lists` - The name of each list and how many tasks it has
+    fn app(lists: &[(&str, usize)]) -> TasksApp {
+        let lists = lists
+            .iter()
+            .map(|(name, count)| {
+                let mut list = List::new(name).unwrap();
+                for i in 0..*count {
+                    list.insert_task(i, Task::new(format!("{} {}", name, i)))
+                        .unwrap();
+                }
+                list
+            })
+            .collect();
+        let document = Document {
+            preamble: vec![],
+            lists,
+        };
+        let file = TaskFile::new("tasks-test.md", document, 0);
+        TasksApp::new(file, &Config::default())
+    }
+
+    /// Presses the keys in a textbox that starts with the given text and the cursor at its end
+    ///
+    /// # Returns
+    ///
+    /// The text afterwards, and the part of it before the cursor
+    fn edit(text: &str, keys: &[KeyCode]) -> (String, String) {
+        let mut text = text.to_string();
+        let mut cursor = text.len();
+        for key in keys {
+            edit_text(&mut text, &mut cursor, *key);
+        }
+        let before = text[..cursor].to_string();
+        (text, before)
+    }
+
+    #[test]
+    fn backspace_deletes_a_whole_grapheme() {
+        let accented = "cafe\u{301}";
+        assert_eq!(edit(accented, &[KeyCode::Backspace]).0, "caf");
+
+        let thumbs_up = "ok 👍🏽";
+        assert_eq!(edit(thumbs_up, &[KeyCode::Backspace]).0, "ok ");
+
+        let family = "👨‍👩‍👧!";
+        assert_eq!(edit(family, &[KeyCode::Left, KeyCode::Backspace]).0, "!");
+    }
+
+    #[test]
+    fn the_cursor_moves_over_a_whole_grapheme() {
+        let (text, before) = edit("a👍🏽b", &[KeyCode::Left, KeyCode::Left]);
+        assert_eq!((text.as_str(), before.as_str()), ("a👍🏽b", "a"));
+
+        let (text, before) = edit("a👍🏽b", &[KeyCode::Left, KeyCode::Left, KeyCode::Right]);
+        assert_eq!((text.as_str(), before.as_str()), ("a👍🏽b", "a👍🏽"));
+    }
+
+    #[test]
+    fn the_cursor_stays_inside_the_text() {
+        let keys = [
+            KeyCode::Left,
+            KeyCode::Left,
+            KeyCode::Backspace,
+            KeyCode::Char('é'),
+        ];
+        assert_eq!(edit("日", &keys), ("é日".to_string(), "é".to_string()));
+
+        let keys = [KeyCode::Right, KeyCode::Char('本')];
+        assert_eq!(edit("日", &keys), ("日本".to_string(), "日本".to_string()));
+    }
+
+    #[test]
+    fn wide_characters_are_cut_short_without_going_over_the_width() {
+        let cut = cut_short("日本語のタスク", 6);
+
+        assert_eq!(cut, "日本…");
+        assert!(visible_width(&cut) <= 6);
+        assert_eq!(cut_short("日本語", 6), "日本語");
+    }
+
+    #[test]
+    fn a_count_before_gg_or_g_goes_to_that_task() {
+        let mut app = app(&[("One", 6), ("Two", 1)]);
+
+        app.perform(Action::GotoBottom, Some(3)).unwrap();
+        assert_eq!(app.current_task_index, 2);
+        app.perform(Action::GotoTop, Some(5)).unwrap();
+        assert_eq!(app.current_task_index, 4);
+        app.perform(Action::GotoTop, None).unwrap();
+        assert_eq!(app.current_task_index, 0);
+        app.perform(Action::GotoBottom, Some(99)).unwrap();
+        assert_eq!(app.current_task_index, 5);
+        app.perform(Action::GotoList, Some(2)).unwrap();
+        assert_eq!(app.current_list_index, 1);
+    }
+
+    #[test]
+    fn a_number_typed_on_its_own_on_the_board_keeps_the_cursor_on_a_task() {
+        let mut app = app(&[("One", 6), ("Two", 1)]);
+        app.toggle_board();
+        app.current_task_index = 5;
+
+        app.goto_numbered_list(2);
+
+        assert_eq!(app.current_list_index, 1);
+        assert_eq!(app.current_task_index, 0);
+    }
+}
@@ -0,0 +1,370 @@
+use colored::Colorize;
+use std::ops::Range;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// The narrowest a column of the board can be before fewer lists are shown at once
+const MIN_COLUMN_WIDTH: usize = 20;
+
+/// What is drawn between the columns of the board
+pub const COLUMN_SEPARATOR: &str = " │ ";
+
+/// The state of the board view, which shows every list side by side as columns
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Board {
+    /// The index of the list in the leftmost column
+    first_column: usize,
+    /// The position of the current task relative to the origin of the app, if it was drawn
+    cursor: Option<(u16, u16)>,
+    /// The number of rows the tasks were drawn in
+    height: usize,
+}
+
+impl Board {
+    /// Works out which lists fit on the screen as columns, scrolling sideways so that the
+    /// current list is one of them
+    ///
+    /// # Arguments
+    ///
+    /// * `current` - The index of the current list
+    /// * `count`   - The number of lists
+    /// * `width`   - The width of the terminal
+    ///
+    /// # Returns
+    ///
+    /// The indexes of the lists shown, along with how wide each column is
+    pub fn columns(&mut self, current: usize, count: usize, width: usize) -> (Range<usize>, usize) {
+        let separator = COLUMN_SEPARATOR.width();
+        let shown = ((width + separator) / (MIN_COLUMN_WIDTH + separator)).clamp(1, count.max(1));
+
+        if current < self.first_column {
+            self.first_column = current;
+        } else if current >= self.first_column + shown {
+            self.first_column = current + 1 - shown;
+        }
+        self.first_column = self.first_column.min(count.saturating_sub(shown));
+
+        let column_width = (width.saturating_sub(separator * (shown - 1)) / shown).max(1);
+        (self.first_column..self.first_column + shown, column_width)
+    }
+
+    /// Remembers where the board was drawn
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The position of the current task relative to the origin of the app
+    /// * `height` - The number of rows the tasks were drawn in
+    pub fn set_layout(&mut self, cursor: Option<(u16, u16)>, height: usize) {
+        self.cursor = cursor;
+        self.height = height;
+    }
+
+    /// Returns the position of the current task relative to the origin of the app, if it was
+    /// drawn
+    pub fn cursor(&self) -> Option<(u16, u16)> {
+        self.cursor
+    }
+
+    /// Returns the number of rows the tasks were drawn in
+    pub fn height(&self) -> usize {
+        self.height
+    }
+}
+
+/// The lines of a single column of the board
+pub struct Column {
+    pub lines: Vec<String>,
+    /// The line the current task starts on, if it is in this column
+    pub cursor_line: Option<usize>,
+}
+
+/// Lays out the tasks of a list as a column, wrapping them to fit its width. If they don't all
+/// fit, then the column scrolls so that the current task is shown
+///
+/// # Arguments
+///
+/// * `tasks`  - Each task as it should be drawn, along with its depth
+/// * `cursor` - The index into `tasks` of the current task, if it is in this column
+/// * `width`  - The width of the column
+/// * `rows`   - The number of rows the column can use
+pub fn layout_column(
+    tasks: &[(String, usize)],
+    cursor: Option<usize>,
+    width: usize,
+    rows: usize,
+) -> Column {
+    // Lines after the first line of a task line up with the text after its checkbox
+    let wrapped: Vec<Vec<String>> = tasks
+        .iter()
+        .map(|(text, depth)| wrap(text, width, 2 * depth + 4))
+        .collect();
+
+    // A cursor past the last task is on the last task
+    let cursor = match (cursor, wrapped.len()) {
+        (Some(cursor), len) if len > 0 => Some(cursor.min(len - 1)),
+        _ => None,
+    };
+    let mut start = 0;
+    if let Some(cursor) = cursor {
+        while start < cursor {
+            let above = usize::from(start > 0);
+            let below = usize::from(cursor + 1 < wrapped.len());
+            let used: usize = wrapped[start..=cursor].iter().map(Vec::len).sum();
+            if above + used + below <= rows {
+                break;
+            }
+            start += 1;
+        }
+    }
+
+    let mut lines = vec![];
+    let mut cursor_line = None;
+    if start > 0 {
+        lines.push(hidden_message(start, "above", width));
+    }
+    for (i, task_lines) in wrapped.iter().enumerate().skip(start) {
+        // A line is kept free to say how many tasks are hidden below, unless this is the last one
+        let reserved = usize::from(i + 1 < wrapped.len());
+        if lines.len() + task_lines.len() + reserved > rows && Some(i) != cursor {
+            lines.push(hidden_message(wrapped.len() - i, "below", width));
+            break;
+        }
+        if Some(i) == cursor {
+            cursor_line = Some(lines.len());
+        }
+        lines.extend(task_lines.iter().cloned());
+    }
+    lines.truncate(rows);
+
+    Column { lines, cursor_line }
+}
+
+/// Formats the message shown when tasks in a column are hidden above or below what is shown
+///
+/// # Arguments
+///
+/// * `count`     - The number of hidden tasks
+/// * `direction` - Where the tasks are hidden, either "above" or "below"
+/// * `width`     - The width of the column
+fn hidden_message(count: usize, direction: &str, width: usize) -> String {
+    truncate(&format!("{} more {}", count, direction), width)
+        .dimmed()
+        .to_string()
+}
+
+/// A piece of styled text, either a character that is shown or an escape sequence that styles
+/// the characters after it
+enum Piece {
+    Char(char),
+    Escape(String),
+}
+
+impl Piece {
+    /// Returns how many columns of the terminal the piece takes up, which is two for wide
+    /// characters such as CJK and most emoji, and none for escape sequences and combining marks
+    fn width(&self) -> usize {
+        match self {
+            Piece::Char(c) => c.width().unwrap_or(0),
+            Piece::Escape(_) => 0,
+        }
+    }
+}
+
+/// Splits styled text into the characters that are shown and the escape sequences between them
+///
+/// # Arguments
+///
+/// * `text` - The text to split
+fn pieces(text: &str) -> Vec<Piece> {
+    let mut pieces = vec![];
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            pieces.push(Piece::Char(c));
+            continue;
+        }
+
+        let mut escape = String::from(c);
+        if chars.peek() == Some(&'[') {
+            escape.push(chars.next().unwrap_or('['));
+            for c in chars.by_ref() {
+                escape.push(c);
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+        pieces.push(Piece::Escape(escape));
+    }
+
+    pieces
+}
+
+/// Returns how many columns of the terminal styled text takes up
+///
+/// # Arguments
+///
+/// * `text` - The text to measure
+pub fn visible_width(text: &str) -> usize {
+    pieces(text).iter().map(Piece::width).sum()
+}
+
+/// Shortens unstyled text so that it fits in the given width, ending it with an ellipsis if any
+/// of it was cut off
+///
+/// # Arguments
+///
+/// * `text`  - The text to shorten
+/// * `width` - The most columns the text can take up
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+
+    let mut truncated = String::new();
+    let mut truncated_width = 0;
+    for c in text.chars() {
+        let c_width = c.width().unwrap_or(0);
+        if truncated_width + c_width >= width {
+            break;
+        }
+        truncated.push(c);
+        truncated_width += c_width;
+    }
+    truncated.push('…');
+    truncated
+}
+
+/// Wraps styled text so that no line is wider than the given width, breaking lines between
+/// words where possible. Styles carry on across the lines they are split over
+///
+/// # Arguments
+///
+/// * `text`  - The text to wrap
+/// * `width` - The most columns a line can take up
+/// * `hang`  - How far lines after the first are indented
+pub fn wrap(text: &str, width: usize, hang: usize) -> Vec<String> {
+    let hang = if hang < width { hang } else { 0 };
+
+    let mut lines: Vec<Vec<Piece>> = vec![];
+    let mut line: Vec<Piece> = vec![];
+    let mut line_width = 0;
+    let max_width = |lines: &Vec<Vec<Piece>>| match lines.is_empty() {
+        true => width,
+        false => width - hang,
+    };
+
+    for piece in pieces(text) {
+        let c_width = piece.width();
+        let c = match piece {
+            Piece::Escape(_) => {
+                line.push(piece);
+                continue;
+            }
+            Piece::Char(c) => c,
+        };
+
+        while line_width > 0 && line_width + c_width > max_width(&lines) {
+            let space = line
+                .iter()
+                .rposition(|piece| matches!(piece, Piece::Char(' ')));
+            match space {
+                Some(space) if space > 0 => {
+                    let rest = line.split_off(space + 1);
+                    line.pop();
+                    lines.push(line);
+                    line_width = rest.iter().map(Piece::width).sum();
+                    line = rest;
+                }
+                _ => {
+                    lines.push(line);
+                    line = vec![];
+                    line_width = 0;
+                }
+            }
+        }
+
+        // Spaces aren't kept at the start of a wrapped line
+        if c == ' ' && line_width == 0 && !lines.is_empty() {
+            continue;
+        }
+        line.push(Piece::Char(c));
+        line_width += c_width;
+    }
+    lines.push(line);
+
+    let mut styles: Vec<String> = vec![];
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let mut text = match i {
+                0 => String::new(),
+                _ => " ".repeat(hang),
+            };
+            text.push_str(&styles.concat());
+            for piece in line {
+                match piece {
+                    Piece::Char(c) => text.push(c),
+                    Piece::Escape(escape) => {
+                        if escape == "\x1b[0m" {
+                            styles.clear();
+                        } else {
+                            styles.push(escape.clone());
+                        }
+                        text.push_str(&escape);
+                    }
+                }
+            }
+            if !styles.is_empty() {
+                text.push_str("\x1b[0m");
+            }
+            text
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates tasks that each fit on one line, at the top level
+    fn tasks(count: usize) -> Vec<(String, usize)> {
+        (0..count).map(|i| (format!("[ ] {}", i), 0)).collect()
+    }
+
+    #[test]
+    fn wide_characters_are_truncated_without_going_over_the_width() {
+        assert_eq!(truncate("日本語テキスト", 7), "日本語…");
+        assert_eq!(truncate("日本語テキスト", 8), "日本語…");
+        assert_eq!(truncate("日本語", 6), "日本語");
+        assert_eq!(truncate("👍🏽👍🏽👍🏽", 4), "👍…");
+    }
+
+    #[test]
+    fn wide_characters_are_wrapped_without_being_split() {
+        assert_eq!(wrap("日本語 テキスト", 6, 0), ["日本語", "テキス", "ト"]);
+        assert_eq!(wrap("日本", 3, 0), ["日", "本"]);
+        assert_eq!(wrap("a 日本語", 6, 2), ["a", "  日本", "  語"]);
+
+        for line in wrap("タスクの説明がとても長い", 7, 2) {
+            assert!(line.width() <= 7, "{} is too wide", line);
+        }
+    }
+
+    #[test]
+    fn a_cursor_past_the_last_task_is_on_the_last_task() {
+        let column = layout_column(&tasks(1), Some(5), 20, 10);
+
+        assert_eq!(column.lines, ["[ ] 0"]);
+        assert_eq!(column.cursor_line, Some(0));
+    }
+
+    #[test]
+    fn a_cursor_in_an_empty_column_is_ignored() {
+        let column = layout_column(&[], Some(3), 20, 10);
+
+        assert!(column.lines.is_empty());
+        assert_eq!(column.cursor_line, None);
+    }
+}
@@ -0,0 +1,321 @@
+use crate::app::keymap::{Action, KeySequence, Keymap, Mode};
+use crate::app::theme::Theme;
+
+use colored::Color;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The name of the list created when a new task file is made, if the config doesn't give one
+const DEFAULT_LIST_NAME: &str = "Main";
+
+/// How many previous versions of the task file are kept, if the config doesn't say
+const DEFAULT_BACKUPS: usize = 1;
+
+/// How many seconds without a key being pressed before the lists are saved, if autosaving when
+/// idle and the config doesn't say
+const DEFAULT_AUTOSAVE_DELAY: u64 = 2;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+/// The settings read from the config file. Anything left out of the file keeps its default
+pub struct Config {
+    /// The name of the list created when a new task file is made
+    default_list_name: Option<String>,
+    /// What is drawn inside the checkbox of a completed task
+    checkmark: Option<String>,
+    /// How many previous versions of the task file are kept as backups
+    backups: Option<usize>,
+    /// When the lists are saved without quitting
+    autosave: Autosave,
+    /// How many seconds without a key being pressed before the lists are saved, when autosaving
+    /// when idle
+    autosave_delay: Option<u64>,
+    /// Whether the app takes up the whole terminal rather than being drawn below the prompt
+    full_screen: bool,
+    colours: Colours,
+    /// Keys to bind to actions, replacing the keys they are bound to by default
+    keys: HashMap<Action, Bindings>,
+    /// The same as `keys`, but for when tasks are being selected
+    visual_keys: HashMap<Action, Bindings>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+/// When the lists are saved, other than when the app is quit
+pub enum Autosave {
+    /// Only when the app is quit
+    #[default]
+    Off,
+    /// After every change
+    Change,
+    /// Once no key has been pressed for a while after a change
+    Idle,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+/// The colours set in the config file
+struct Colours {
+    checkmark: Option<Colour>,
+    overdue: Option<Colour>,
+    due_today: Option<Colour>,
+    tag: Option<Colour>,
+    priority_a: Option<Colour>,
+    priority_b: Option<Colour>,
+    priority_c: Option<Colour>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(try_from = "String")]
+/// A colour written by name in the config file, e.g. `red` or `bright blue`
+struct Colour(Color);
+
+impl TryFrom<String> for Colour {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.replace('_', " ")
+            .parse()
+            .map(Colour)
+            .map_err(|_| format!("unknown colour \"{}\"", name))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "toml::Value")]
+/// The keys bound to an action in the config file, either a single sequence of keys or a list of
+/// them
+struct Bindings(Vec<KeySequence>);
+
+impl TryFrom<toml::Value> for Bindings {
+    type Error = String;
+
+    fn try_from(value: toml::Value) -> Result<Self, Self::Error> {
+        let values = match value {
+            toml::Value::Array(values) => values,
+            value => vec![value],
+        };
+
+        values
+            .into_iter()
+            .map(|value| match value {
+                toml::Value::String(keys) => KeySequence::try_from(keys),
+                _ => Err("keys must be a string or a list of strings".to_string()),
+            })
+            .collect::<Result<Vec<KeySequence>, String>>()
+            .map(Bindings)
+    }
+}
+
+impl Config {
+    /// Returns the name of the list created when a new task file is made
+    pub fn default_list_name(&self) -> String {
+        self.default_list_name
+            .clone()
+            .unwrap_or(DEFAULT_LIST_NAME.to_string())
+    }
+
+    /// Returns how many previous versions of the task file are kept as backups
+    pub fn backups(&self) -> usize {
+        self.backups.unwrap_or(DEFAULT_BACKUPS)
+    }
+
+    /// Returns when the lists are saved, other than when the app is quit
+    pub fn autosave(&self) -> Autosave {
+        self.autosave
+    }
+
+    /// Returns how long to wait without a key being pressed before saving, when autosaving when
+    /// idle
+    pub fn autosave_delay(&self) -> Duration {
+        Duration::from_secs(self.autosave_delay.unwrap_or(DEFAULT_AUTOSAVE_DELAY))
+    }
+
+    /// Returns whether the app takes up the whole terminal rather than being drawn below the
+    /// prompt
+    pub fn full_screen(&self) -> bool {
+        self.full_screen
+    }
+
+    /// Sets whether the app takes up the whole terminal, overriding the config file
+    ///
+    /// # Arguments
+    ///
+    /// * `full_screen` - Whether the app takes up the whole terminal
+    pub fn set_full_screen(&mut self, full_screen: bool) {
+        self.full_screen = full_screen;
+    }
+
+    /// Returns the theme with the glyphs and colours set in the config
+    pub fn theme(&self) -> Theme {
+        let default = Theme::default();
+        let colour = |colour: Option<Colour>, default: Color| colour.map_or(default, |c| c.0);
+
+        Theme {
+            checkmark: self.checkmark.clone().unwrap_or(default.checkmark),
+            checkmark_colour: colour(self.colours.checkmark, default.checkmark_colour),
+            overdue: colour(self.colours.overdue, default.overdue),
+            due_today: colour(self.colours.due_today, default.due_today),
+            tag: colour(self.colours.tag, default.tag),
+            priority_a: colour(self.colours.priority_a, default.priority_a),
+            priority_b: colour(self.colours.priority_b, default.priority_b),
+            priority_c: colour(self.colours.priority_c, default.priority_c),
+        }
+    }
+
+    /// Checks that the config doesn't bind the same keys to two actions in the same mode, as which
+    /// of them the keys carried out would depend on the order the bindings were read in
+    ///
+    /// # Returns
+    ///
+    /// A message saying which keys are bound twice, if any are
+    fn check_keys(&self) -> Result<(), String> {
+        for (table, keys) in [("keys", &self.keys), ("visual_keys", &self.visual_keys)] {
+            let mut bound: Vec<(&KeySequence, Action)> = vec![];
+            for (action, bindings) in keys {
+                for sequence in &bindings.0 {
+                    if bound
+                        .iter()
+                        .any(|(keys, other)| *keys == sequence && other != action)
+                    {
+                        let keys: String = sequence.0.iter().map(|key| key.to_string()).collect();
+                        return Err(format!(
+                            "\"{}\" is bound to more than one action in [{}]",
+                            keys, table
+                        ));
+                    }
+                    bound.push((sequence, *action));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the default keymap with the keys set in the config bound
+    pub fn keymap(&self) -> Keymap {
+        let mut keymap = Keymap::default();
+
+        for (mode, keys) in [
+            (Mode::Normal, &self.keys),
+            (Mode::Visual, &self.visual_keys),
+        ] {
+            for (action, bindings) in keys {
+                let keys = bindings.0.iter().map(|keys| keys.0.clone()).collect();
+                keymap.rebind(mode, *action, keys);
+            }
+        }
+        keymap
+    }
+}
+
+/// Returns the path of the config file, which is `tasks/config.toml` in `$XDG_CONFIG_HOME`, or in
+/// `~/.config` if that isn't set
+pub fn config_path() -> Option<PathBuf> {
+    let config_dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+
+    Some(config_dir.join("tasks").join("config.toml"))
+}
+
+/// Reads the config file
+///
+/// # Returns
+///
+/// The config, which is the default config if there is no config file, or a message saying what
+/// is wrong with the file
+pub fn load_config() -> Result<Config, String> {
+    let path = match config_path() {
+        Some(path) => path,
+        None => return Ok(Config::default()),
+    };
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+
+    parse_config(&contents).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Parses the contents of a config file
+///
+/// # Arguments
+///
+/// * `contents` - The text of the config file
+///
+/// # Returns
+///
+/// The config, or a message saying what is wrong with it
+fn parse_config(contents: &str) -> Result<Config, String> {
+    let config: Config = toml::from_str(contents).map_err(|e| e.to_string())?;
+    config.check_keys()?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::keymap::{parse_keys, Lookup};
+
+    /// Returns the action the keys carry out in normal mode, if they are bound to one
+    fn action(keymap: &Keymap, keys: &str) -> Option<Action> {
+        match keymap.lookup(Mode::Normal, &parse_keys(keys).unwrap()) {
+            Lookup::Action(action) => Some(action),
+            Lookup::Pending | Lookup::Unbound => None,
+        }
+    }
+
+    #[test]
+    fn binding_keys_replaces_the_default_ones() {
+        let config = parse_config("[keys]\nnext_task = [\"n\", \"<C-n>\"]\n").unwrap();
+        let keymap = config.keymap();
+
+        assert_eq!(action(&keymap, "n"), Some(Action::NextTask));
+        assert_eq!(action(&keymap, "<C-n>"), Some(Action::NextTask));
+        assert_eq!(action(&keymap, "j"), None);
+        assert_eq!(action(&keymap, "<Down>"), None);
+    }
+
+    #[test]
+    fn keys_taken_from_a_default_binding_are_unbound_from_it() {
+        let config = parse_config("[keys]\nnew_task = \"dd\"\n").unwrap();
+        let keymap = config.keymap();
+
+        assert_eq!(action(&keymap, "dd"), Some(Action::NewTask));
+        assert_eq!(action(&keymap, "o"), None);
+        assert_eq!(action(&keymap, "dc"), Some(Action::DeleteCompletedTasks));
+    }
+
+    #[test]
+    fn keys_cant_be_bound_to_two_actions_in_the_config() {
+        let error = parse_config("[keys]\nnext_task = \"x\"\nprev_task = [\"k\", \"x\"]\n");
+        assert_eq!(
+            error.unwrap_err(),
+            "\"x\" is bound to more than one action in [keys]"
+        );
+
+        let config = "[keys]\nnext_task = \"x\"\n[visual_keys]\nvisual = [\"x\", \"x\"]\n";
+        assert!(parse_config(config).is_ok());
+    }
+
+    #[test]
+    fn unknown_keys_and_actions_are_reported() {
+        let error = parse_config("[keys]\nnext_task = \"<Nope>\"\n").unwrap_err();
+        assert!(
+            error.contains("unknown key <Nope> in \"<Nope>\""),
+            "{}",
+            error
+        );
+
+        assert!(parse_config("[keys]\nfly = \"f\"\n").is_err());
+        assert!(parse_config("[keys]\nnext_task = 1\n").is_err());
+    }
+}
@@ -0,0 +1,176 @@
+use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
+
+/// The format dates are written in
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// The prefix of a due date written as a tag, e.g. `due:2026-10-20`
+const DUE_TAG: &str = "due:";
+
+/// The emoji Obsidian's tasks plugin writes before a due date, e.g. `📅 2026-10-20`
+const DUE_EMOJI: &str = "📅";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// The way a due date is written in the task file
+pub enum DueFormat {
+    /// `due:2026-10-20`
+    #[default]
+    Tag,
+    /// `📅 2026-10-20`
+    Emoji,
+}
+
+impl DueFormat {
+    /// Formats the given date as it should be written in the task file
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - The date to format
+    pub fn format(&self, date: NaiveDate) -> String {
+        match self {
+            DueFormat::Tag => format!("{}{}", DUE_TAG, date.format(DATE_FORMAT)),
+            DueFormat::Emoji => format!("{} {}", DUE_EMOJI, date.format(DATE_FORMAT)),
+        }
+    }
+}
+
+/// Returns the current date in the local timezone
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Finds a due date written in a task description, in either the `due:2026-10-20` or
+/// `📅 2026-10-20` format
+///
+/// # Arguments
+///
+/// * `text` - The text of the task
+///
+/// # Returns
+///
+/// The text with the due date removed, along with the due date and the format it was written in
+/// if one was found
+pub fn extract_due_date(text: &str) -> (String, Option<(NaiveDate, DueFormat)>) {
+    let words: Vec<&str> = text.split(' ').collect();
+
+    for (i, word) in words.iter().enumerate() {
+        let (date, len) = if let Some(date) = word.strip_prefix(DUE_TAG) {
+            (date, 1)
+        } else if *word == DUE_EMOJI && i + 1 < words.len() {
+            (words[i + 1], 2)
+        } else {
+            continue;
+        };
+
+        let format = match len {
+            1 => DueFormat::Tag,
+            _ => DueFormat::Emoji,
+        };
+
+        if let Ok(date) = NaiveDate::parse_from_str(date, DATE_FORMAT) {
+            let remaining = words[..i]
+                .iter()
+                .chain(&words[i + len..])
+                .copied()
+                .collect::<Vec<&str>>()
+                .join(" ");
+            return (remaining.trim().to_string(), Some((date, format)));
+        }
+    }
+
+    (text.to_string(), None)
+}
+
+/// Parses a date typed in by the user. As well as dates in the `2026-10-20` format, the following
+/// relative dates are understood
+/// - `today`, `tomorrow` and `yesterday`
+/// - Day names such as `fri` or `friday`, meaning the next one after today
+/// - Offsets from today such as `+3d`, `+2w` or `+3`, which is taken as days
+///
+/// # Arguments
+///
+/// * `input` - The text the user typed
+/// * `today` - The date relative dates are worked out from
+///
+/// # Returns
+///
+/// The date, or None if the input isn't understood
+pub fn parse_date_input(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let input = input.trim().to_lowercase();
+
+    match input.as_str() {
+        "today" | "tod" => return Some(today),
+        "tomorrow" | "tom" => return Some(today + Duration::days(1)),
+        "yesterday" => return Some(today - Duration::days(1)),
+        _ => (),
+    }
+
+    if let Some(offset) = input.strip_prefix('+') {
+        let (amount, days_per_unit) = match offset.chars().last()? {
+            'd' => (&offset[..offset.len() - 1], 1),
+            'w' => (&offset[..offset.len() - 1], 7),
+            _ => (offset, 1),
+        };
+        let amount: i64 = amount.parse().ok()?;
+        let days = Duration::try_days(amount.checked_mul(days_per_unit)?)?;
+        return today.checked_add_signed(days);
+    }
+
+    if let Some(weekday) = parse_weekday(&input) {
+        let days_ahead = (7 + weekday.num_days_from_monday() as i64
+            - today.weekday().num_days_from_monday() as i64)
+            % 7;
+        let days_ahead = if days_ahead == 0 { 7 } else { days_ahead };
+        return Some(today + Duration::days(days_ahead));
+    }
+
+    NaiveDate::parse_from_str(&input, DATE_FORMAT).ok()
+}
+
+/// Parses the name of a day, either in full or shortened to its first three letters
+///
+/// # Arguments
+///
+/// * `input` - The lowercase name of the day
+fn parse_weekday(input: &str) -> Option<Weekday> {
+    let weekdays = [
+        ("monday", Weekday::Mon),
+        ("tuesday", Weekday::Tue),
+        ("wednesday", Weekday::Wed),
+        ("thursday", Weekday::Thu),
+        ("friday", Weekday::Fri),
+        ("saturday", Weekday::Sat),
+        ("sunday", Weekday::Sun),
+    ];
+
+    weekdays
+        .iter()
+        .find(|(name, _)| input.len() >= 3 && name.starts_with(input))
+        .map(|(_, weekday)| *weekday)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn offsets_are_added_to_today() {
+        let today = day(2024, 1, 31);
+
+        assert_eq!(parse_date_input("+3", today), Some(day(2024, 2, 3)));
+        assert_eq!(parse_date_input("+2d", today), Some(day(2024, 2, 2)));
+        assert_eq!(parse_date_input("+1w", today), Some(day(2024, 2, 7)));
+    }
+
+    #[test]
+    fn offsets_too_large_for_a_date_are_not_understood() {
+        let today = day(2024, 1, 31);
+
+        assert_eq!(parse_date_input("+99999999999999d", today), None);
+        assert_eq!(parse_date_input("+9223372036854775807w", today), None);
+        assert_eq!(parse_date_input("+999999999w", today), None);
+    }
+}
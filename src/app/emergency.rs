@@ -0,0 +1,85 @@
+use crate::app::guard::restore_terminal;
+use crate::app::serialisation::{recovery_path, serialise, Document};
+use signal_hook::consts::{SIGINT, SIGTERM};
+use signal_hook::iterator::Signals;
+use std::io;
+use std::panic;
+use std::path::Path;
+use std::process;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// The signals that save any unsaved changes before the app exits. SIGHUP is sent when the
+/// terminal the app is running in is closed
+#[cfg(unix)]
+const SIGNALS: [i32; 3] = [signal_hook::consts::SIGHUP, SIGINT, SIGTERM];
+#[cfg(not(unix))]
+const SIGNALS: [i32; 2] = [SIGINT, SIGTERM];
+
+#[derive(Clone, Default)]
+/// The changes made to the lists that haven't been saved, kept where they can still be saved if
+/// the app is killed or panics
+pub struct Unsaved(Arc<Mutex<Option<Document>>>);
+
+impl Unsaved {
+    /// Sets the changes that haven't been saved
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document as it would be saved, or None if there is nothing unsaved
+    pub fn set(&self, document: Option<Document>) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = document;
+    }
+}
+
+/// Restores the terminal and writes any unsaved changes to the recovery file if the app is sent
+/// a signal to exit, or if it panics
+///
+/// # Arguments
+///
+/// * `path`    - The path of the task file
+/// * `unsaved` - The changes that haven't been saved
+pub fn save_on_exit(path: &Path, unsaved: &Unsaved) -> io::Result<()> {
+    let mut signals = Signals::new(SIGNALS)?;
+    let (signal_path, signal_unsaved) = (path.to_path_buf(), unsaved.clone());
+    thread::spawn(move || {
+        if let Some(signal) = signals.forever().next() {
+            restore_terminal();
+            emergency_save(&signal_path, &signal_unsaved);
+            process::exit(128 + signal);
+        }
+    });
+
+    let (path, unsaved) = (path.to_path_buf(), unsaved.clone());
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        default_hook(info);
+        emergency_save(&path, &unsaved);
+    }));
+    Ok(())
+}
+
+/// Writes the unsaved changes, if there are any, to the recovery file next to the task file
+///
+/// # Arguments
+///
+/// * `path`    - The path of the task file
+/// * `unsaved` - The changes that haven't been saved
+pub fn emergency_save(path: &Path, unsaved: &Unsaved) {
+    let document = unsaved.0.lock().unwrap_or_else(|e| e.into_inner());
+    let document = match document.as_ref() {
+        Some(document) => document,
+        None => return,
+    };
+
+    let recovery = recovery_path(path);
+    match serialise(&recovery, document, 0) {
+        Ok(()) => eprintln!("\nUnsaved changes were written to {}", recovery.display()),
+        Err(e) => eprintln!(
+            "\nError: Couldn't write unsaved changes to {}: {}",
+            recovery.display(),
+            e
+        ),
+    }
+}
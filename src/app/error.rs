@@ -0,0 +1,54 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+/// The ways working with lists, tasks and task files can fail
+pub enum TasksError {
+    /// A list was given an empty name
+    EmptyName,
+    /// A task was given an empty description
+    EmptyDescription,
+    /// There is no task at the index given
+    IndexOutOfRange { index: usize, len: usize },
+    /// The task file couldn't be read or written
+    Io(io::Error),
+    /// A line of the task file couldn't be understood
+    Parse { line: usize, reason: String },
+    /// The task file doesn't have any lists in it
+    NoLists,
+}
+
+impl fmt::Display for TasksError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TasksError::EmptyName => write!(f, "The name of a list can't be empty"),
+            TasksError::EmptyDescription => write!(f, "The description of a task can't be empty"),
+            TasksError::IndexOutOfRange { index, len } => write!(
+                f,
+                "There is no task at index {}, the list only has {} task{}",
+                index,
+                len,
+                if *len == 1 { "" } else { "s" }
+            ),
+            TasksError::Io(e) => write!(f, "{}", e),
+            TasksError::Parse { line, reason } => write!(f, "Line {}: {}", line, reason),
+            TasksError::NoLists => write!(f, "There are no lists in it"),
+        }
+    }
+}
+
+impl Error for TasksError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TasksError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TasksError {
+    fn from(e: io::Error) -> Self {
+        TasksError::Io(e)
+    }
+}
@@ -0,0 +1,51 @@
+use crossterm::{
+    cursor, execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    Result,
+};
+use std::io::stdout;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the app is drawn on the alternate screen, so that it can be left however the app stops
+static ALTERNATE_SCREEN: AtomicBool = AtomicBool::new(false);
+
+/// Puts the terminal into raw mode until it is dropped, so that the terminal is restored however
+/// the app stops, including by returning an error
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    /// Puts the terminal into raw mode
+    ///
+    /// # Arguments
+    ///
+    /// * `alternate_screen` - Whether to also switch to the alternate screen, which is left when
+    ///   the terminal is restored
+    pub fn new(alternate_screen: bool) -> Result<Self> {
+        enable_raw_mode()?;
+        if alternate_screen {
+            execute!(stdout(), EnterAlternateScreen)?;
+            ALTERNATE_SCREEN.store(true, Ordering::SeqCst);
+        }
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+/// Takes the terminal out of raw mode and the alternate screen, and puts the cursor back to how it
+/// was. Errors are ignored, as this is used when the app is already stopping
+pub fn restore_terminal() {
+    if ALTERNATE_SCREEN.swap(false, Ordering::SeqCst) {
+        let _ = execute!(stdout(), LeaveAlternateScreen);
+    }
+    let _ = disable_raw_mode();
+    let _ = execute!(
+        stdout(),
+        cursor::SetCursorStyle::DefaultUserShape,
+        cursor::Show
+    );
+}
@@ -0,0 +1,135 @@
+use crate::app::task::List;
+
+/// The maximum number of changes that can be undone
+const HISTORY_LIMIT: usize = 100;
+
+#[derive(Clone, Debug)]
+/// The state of the app at a point in time
+pub struct Snapshot {
+    pub lists: Vec<List>,
+    pub current_list_index: usize,
+    pub current_task_index: usize,
+}
+
+#[derive(Default)]
+/// A record of the changes made to the lists, allowing them to be undone and redone
+pub struct History {
+    undo_stack: Vec<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+}
+
+impl History {
+    /// Records the state of the app from before a change was made. Any changes that were undone
+    /// can no longer be redone after this
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot` - The state of the app before the change
+    pub fn record(&mut self, snapshot: Snapshot) {
+        self.undo_stack.push(snapshot);
+        self.redo_stack.clear();
+
+        if self.undo_stack.len() > HISTORY_LIMIT {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    /// Steps back to the state before the last change
+    ///
+    /// # Arguments
+    ///
+    /// * `current` - The current state of the app, so that the undo can be redone
+    ///
+    /// # Returns
+    ///
+    /// The state to restore, or None if there is nothing to undo
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let snapshot = self.undo_stack.pop()?;
+        self.redo_stack.push(current);
+        Some(snapshot)
+    }
+
+    /// Steps forward to the state before the last undo
+    ///
+    /// # Arguments
+    ///
+    /// * `current` - The current state of the app, so that the redo can be undone
+    ///
+    /// # Returns
+    ///
+    /// The state to restore, or None if there is nothing to redo
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let snapshot = self.redo_stack.pop()?;
+        self.undo_stack.push(current);
+        Some(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a snapshot that can be told apart from others by its task index
+    fn snapshot(id: usize) -> Snapshot {
+        Snapshot {
+            lists: vec![],
+            current_list_index: 0,
+            current_task_index: id,
+        }
+    }
+
+    /// Returns the id of a snapshot returned by undo or redo, if there was one
+    fn id(snapshot: Option<Snapshot>) -> Option<usize> {
+        snapshot.map(|snapshot| snapshot.current_task_index)
+    }
+
+    #[test]
+    fn there_is_nothing_to_undo_or_redo_at_first() {
+        let mut history = History::default();
+        assert_eq!(id(history.undo(snapshot(0))), None);
+        assert_eq!(id(history.redo(snapshot(0))), None);
+    }
+
+    #[test]
+    fn changes_are_undone_and_redone_in_order() {
+        let mut history = History::default();
+        history.record(snapshot(0));
+        history.record(snapshot(1));
+
+        assert_eq!(id(history.undo(snapshot(2))), Some(1));
+        assert_eq!(id(history.undo(snapshot(1))), Some(0));
+        assert_eq!(id(history.undo(snapshot(0))), None);
+
+        assert_eq!(id(history.redo(snapshot(0))), Some(1));
+        assert_eq!(id(history.redo(snapshot(1))), Some(2));
+        assert_eq!(id(history.redo(snapshot(2))), None);
+
+        assert_eq!(id(history.undo(snapshot(2))), Some(1));
+    }
+
+    #[test]
+    fn a_new_change_cant_be_followed_by_a_redo() {
+        let mut history = History::default();
+        history.record(snapshot(0));
+        assert_eq!(id(history.undo(snapshot(1))), Some(0));
+
+        history.record(snapshot(0));
+        assert_eq!(id(history.redo(snapshot(2))), None);
+        assert_eq!(id(history.undo(snapshot(2))), Some(0));
+    }
+
+    #[test]
+    fn only_the_most_recent_changes_are_kept() {
+        let mut history = History::default();
+        for i in 0..HISTORY_LIMIT + 5 {
+            history.record(snapshot(i));
+        }
+
+        let mut undone = vec![];
+        while let Some(restored) = history.undo(snapshot(0)) {
+            undone.push(restored.current_task_index);
+        }
+        assert_eq!(undone.len(), HISTORY_LIMIT);
+        assert_eq!(undone.last(), Some(&5));
+    }
+}
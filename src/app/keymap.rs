@@ -0,0 +1,917 @@
+use crate::app::registers::is_register;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::fmt;
+
+/// The largest count that can be typed before a command
+const MAX_COUNT: usize = 9999;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// Something the user can do in the app by pressing keys. In the config file actions are written
+/// in snake case, e.g. `next_task`
+pub enum Action {
+    NextTask,
+    PrevTask,
+    NextList,
+    PrevList,
+    GotoList,
+    GotoTop,
+    GotoBottom,
+    GotoScreenTop,
+    GotoScreenMiddle,
+    GotoScreenBottom,
+    MoveListLeft,
+    MoveListRight,
+    ManageLists,
+    ToggleBoard,
+    Visual,
+    YankSelection,
+    DeleteSelection,
+    ToggleSelection,
+    MoveSelectionToNextList,
+    MoveSelectionToPrevList,
+    PasteOverSelection,
+    MoveTaskToNextList,
+    MoveTaskToPrevList,
+    MoveTaskDown,
+    MoveTaskUp,
+    ToggleTask,
+    NewTask,
+    NewList,
+    RewordTask,
+    RenameList,
+    DeleteTask,
+    DeleteAllTasks,
+    DeleteCompletedTasks,
+    DeleteCompletedTasksOnAllLists,
+    DeleteList,
+    YankTask,
+    YankList,
+    PasteBelow,
+    PasteAbove,
+    IndentTask,
+    OutdentTask,
+    ToggleFold,
+    FoldAll,
+    UnfoldAll,
+    SetDueDate,
+    RaisePriority,
+    LowerPriority,
+    SortList,
+    SortAllLists,
+    FilterList,
+    FilterAllLists,
+    Search,
+    NextMatch,
+    PrevMatch,
+    RepeatLastChange,
+    Undo,
+    Redo,
+    Help,
+    Quit,
+    QuitWithoutSaving,
+}
+
+impl Action {
+    /// Returns what the action does, as shown in the help menu
+    pub fn description(&self) -> &'static str {
+        use Action::*;
+
+        match self {
+            NextTask => "Move to the next task",
+            PrevTask => "Move to the previous task",
+            NextList => "Move to the next list",
+            PrevList => "Move to the previous list",
+            GotoList => "Move to the next list, or the list numbered by the count",
+            GotoTop => "Goto the first task, or the task numbered by the count",
+            GotoBottom => "Goto the last task, or the task numbered by the count",
+            GotoScreenTop => "Goto the top task on the screen",
+            GotoScreenMiddle => "Goto the middle task on the screen",
+            GotoScreenBottom => "Goto the bottom task on the screen",
+            MoveListLeft => "Move the current list left",
+            MoveListRight => "Move the current list right",
+            ManageLists => "Manage lists",
+            ToggleBoard => "Show or hide the board, which shows every list side by side",
+            Visual => "Start or stop selecting tasks",
+            YankSelection => "Yank the selected tasks",
+            DeleteSelection => "Delete the selected tasks",
+            ToggleSelection => "Toggle the status of the selected tasks",
+            MoveSelectionToNextList => "Move the selected tasks to the next list",
+            MoveSelectionToPrevList => "Move the selected tasks to the previous list",
+            PasteOverSelection => "Replace the selected tasks with the clipboard",
+            MoveTaskToNextList => "Move current task to the next list",
+            MoveTaskToPrevList => "Move current task to the previous list",
+            MoveTaskDown => "Move current task down the list",
+            MoveTaskUp => "Move current task up the list",
+            ToggleTask => "Toggle current tasks status",
+            NewTask => "Create new task",
+            NewList => "Create new list",
+            RewordTask => "Reword current task",
+            RenameList => "Rename current list",
+            DeleteTask => "Delete current task",
+            DeleteAllTasks => "Delete all tasks from the current list",
+            DeleteCompletedTasks => "Delete completed tasks from the current list",
+            DeleteCompletedTasksOnAllLists => "Delete completed tasks from all lists",
+            DeleteList => "Delete current list",
+            YankTask => "Yank current task",
+            YankList => "Yank all tasks in the current list",
+            PasteBelow => "Paste task/s in the clipboard below",
+            PasteAbove => "Paste task/s in the clipboard above",
+            IndentTask => "Make the current task a subtask of the one above it",
+            OutdentTask => "Move the current task out of its parent task",
+            ToggleFold => "Fold or unfold the current tasks subtasks",
+            FoldAll => "Fold all tasks in the current list",
+            UnfoldAll => "Unfold all tasks in the current list",
+            SetDueDate => "Set or clear the due date of the current task",
+            RaisePriority => "Raise the priority of the current task",
+            LowerPriority => "Lower the priority of the current task",
+            SortList => "Sorts the current list, followed by what to sort by",
+            SortAllLists => "Sorts all lists, followed by what to sort by",
+            FilterList => "Only show tasks in the current list with a tag",
+            FilterAllLists => "Only show tasks in all lists with a tag",
+            Search => "Search all lists",
+            NextMatch => "Move to the next search match",
+            PrevMatch => "Move to the previous search match",
+            RepeatLastChange => "Repeat the last change",
+            Undo => "Undo the last change",
+            Redo => "Redo the last undone change",
+            Help => "Show this menu",
+            Quit => "Quit",
+            QuitWithoutSaving => "Quit without saving changes",
+        }
+    }
+
+    /// Returns whether a count typed before the action means the action is carried out that many
+    /// times, e.g. `5j` moving down five tasks
+    pub fn repeats_with_count(&self) -> bool {
+        use Action::*;
+
+        matches!(
+            self,
+            NextTask
+                | PrevTask
+                | NextList
+                | PrevList
+                | MoveTaskToNextList
+                | MoveTaskToPrevList
+                | MoveTaskDown
+                | MoveTaskUp
+                | MoveListLeft
+                | MoveListRight
+                | IndentTask
+                | OutdentTask
+                | RaisePriority
+                | LowerPriority
+                | NextMatch
+                | PrevMatch
+                | Undo
+                | Redo
+        )
+    }
+
+    /// Returns whether the action acts on the current task, so it can't be carried out when no
+    /// task is shown
+    pub fn needs_task(&self) -> bool {
+        use Action::*;
+
+        matches!(
+            self,
+            MoveTaskToNextList
+                | MoveTaskToPrevList
+                | MoveTaskDown
+                | MoveTaskUp
+                | ToggleTask
+                | RewordTask
+                | DeleteTask
+                | YankTask
+                | IndentTask
+                | OutdentTask
+                | ToggleFold
+                | SetDueDate
+                | RaisePriority
+                | LowerPriority
+        )
+    }
+
+    /// Returns whether the change the action makes depends on what is typed into a prompt, such
+    /// as the description of a new task, so it can't be repeated with `.`
+    pub fn asks_for_input(&self) -> bool {
+        use Action::*;
+
+        matches!(
+            self,
+            NewTask
+                | NewList
+                | RewordTask
+                | RenameList
+                | SetDueDate
+                | SortList
+                | SortAllLists
+                | ManageLists
+        )
+    }
+
+    /// Returns whether the action acts on the tasks selected in visual mode
+    pub fn acts_on_selection(&self) -> bool {
+        use Action::*;
+
+        matches!(
+            self,
+            YankSelection
+                | DeleteSelection
+                | ToggleSelection
+                | MoveSelectionToNextList
+                | MoveSelectionToPrevList
+                | PasteOverSelection
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// A single key press, with any modifiers held
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    /// Creates a key without any modifiers
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The key that is pressed
+    pub fn new(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    /// Creates a key that is pressed while holding control
+    ///
+    /// # Arguments
+    ///
+    /// * `c` - The character that is pressed
+    pub fn ctrl(c: char) -> Self {
+        Self {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    /// Creates a key that is pressed while holding alt
+    ///
+    /// # Arguments
+    ///
+    /// * `c` - The character that is pressed
+    pub fn alt(c: char) -> Self {
+        Self {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::ALT,
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Up => "Up".to_string(),
+            KeyCode::Down => "Down".to_string(),
+            KeyCode::Left => "Left".to_string(),
+            KeyCode::Right => "Right".to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::Backspace => "BS".to_string(),
+            KeyCode::Delete => "Del".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::PageUp => "PageUp".to_string(),
+            KeyCode::PageDown => "PageDown".to_string(),
+            code => format!("{:?}", code),
+        };
+
+        let mut prefix = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            prefix.push_str("C-");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            prefix.push_str("A-");
+        }
+
+        match (prefix.is_empty(), name.chars().count()) {
+            (true, 1) => write!(f, "{}", name),
+            _ => write!(f, "<{}{}>", prefix, name),
+        }
+    }
+}
+
+/// Parses the name of a key written inside angle brackets in the config file, e.g. `Down` or `C-r`
+///
+/// # Arguments
+///
+/// * `name` - The text between the angle brackets
+fn parse_key_name(name: &str) -> Option<Key> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = name;
+    loop {
+        if let Some(stripped) = rest.strip_prefix("C-") {
+            modifiers |= KeyModifiers::CONTROL;
+            rest = stripped;
+        } else if let Some(stripped) = rest.strip_prefix("A-") {
+            modifiers |= KeyModifiers::ALT;
+            rest = stripped;
+        } else {
+            break;
+        }
+    }
+
+    let code = match rest.to_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "lt" => KeyCode::Char('<'),
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "enter" | "cr" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "bs" | "backspace" => KeyCode::Backspace,
+        "del" | "delete" => KeyCode::Delete,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        _ => {
+            let mut chars = rest.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                _ => return None,
+            }
+        }
+    };
+
+    Some(Key { code, modifiers })
+}
+
+/// Parses a sequence of keys written the way vim writes them, e.g. `dd`, `<C-r>` or `z<Space>`.
+/// A `<` that doesn't start the name of a key is the `<` key, so `<<` is pressing it twice
+///
+/// # Arguments
+///
+/// * `text` - The keys to parse
+///
+/// # Returns
+///
+/// The keys, or a message saying why they couldn't be parsed
+pub fn parse_keys(text: &str) -> Result<Vec<Key>, String> {
+    let mut keys = vec![];
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+
+        let name = match (c, rest.find('>')) {
+            ('<', Some(end)) if !rest[..end].contains('<') && end > 0 => &rest[..end],
+            _ => {
+                keys.push(Key::new(KeyCode::Char(c)));
+                continue;
+            }
+        };
+        match parse_key_name(name) {
+            Some(key) => keys.push(key),
+            None => return Err(format!("unknown key <{}> in \"{}\"", name, text)),
+        }
+        rest = &rest[name.len() + 1..];
+    }
+
+    match keys.is_empty() {
+        true => Err("keys can't be empty".to_string()),
+        false => Ok(keys),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+/// A sequence of keys bound to an action in the config file
+pub struct KeySequence(pub Vec<Key>);
+
+impl TryFrom<String> for KeySequence {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        parse_keys(&text).map(KeySequence)
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        let mut modifiers = event.modifiers;
+        // Shift is already part of the character typed, e.g. 'N'
+        if let KeyCode::Char(_) = event.code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+
+        Self {
+            code: event.code,
+            modifiers,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// The set of bindings keys are looked up in
+pub enum Mode {
+    Normal,
+    /// Selecting a range of tasks
+    Visual,
+}
+
+/// The result of looking up the keys pressed so far in the keymap
+pub enum Lookup {
+    /// The keys are bound to the action
+    Action(Action),
+    /// The keys are the start of at least one binding, so more keys are needed
+    Pending,
+    /// The keys aren't bound to anything
+    Unbound,
+}
+
+/// What the keys typed so far make up
+#[derive(Debug, PartialEq, Eq)]
+pub enum Input {
+    /// A whole command, made of an action along with the count and register typed before it if
+    /// there were any
+    Command(Action, Option<usize>, Option<char>),
+    /// The start of a command, so more keys are needed
+    Pending,
+    /// Keys that aren't bound to anything, which have been thrown away
+    Unbound,
+}
+
+/// The keys typed that don't make up a whole command yet, such as the count in `5j`, the register
+/// in `"ayy` or the first `d` of `dd`
+#[derive(Debug, Default)]
+pub struct PendingInput {
+    count: Option<usize>,
+    register: Option<char>,
+    /// Whether `"` has been typed, so the next key is the name of a register
+    naming_register: bool,
+    keys: Vec<Key>,
+}
+
+impl PendingInput {
+    /// Adds a key to the keys typed so far
+    ///
+    /// # Arguments
+    ///
+    /// * `keymap` - The keymap to look the keys up in
+    /// * `mode`   - The mode the app is in
+    /// * `key`    - The key that was pressed
+    ///
+    /// # Returns
+    ///
+    /// What the keys typed so far make up. Once they make up a command or aren't bound to
+    /// anything, the next key starts a new command
+    pub fn push(&mut self, keymap: &Keymap, mode: Mode, key: Key) -> Input {
+        if self.naming_register {
+            self.naming_register = false;
+            return match key.code {
+                // `""` names the unnamed register, which is the same as not naming one
+                KeyCode::Char('"') => Input::Pending,
+                KeyCode::Char(c) if is_register(c) => {
+                    self.register = Some(c);
+                    Input::Pending
+                }
+                _ => {
+                    self.clear();
+                    Input::Unbound
+                }
+            };
+        }
+
+        if self.keys.is_empty() && key == Key::new(KeyCode::Char('"')) {
+            self.naming_register = true;
+            return Input::Pending;
+        }
+
+        // Digits before the rest of a command are its count, although a count can't start with 0
+        if let (true, KeyCode::Char(c)) = (self.keys.is_empty(), key.code) {
+            let digit = c.to_digit(10).map(|digit| digit as usize);
+            match (digit, self.count) {
+                (Some(0), None) | (None, _) => (),
+                (Some(digit), count) if key.modifiers == KeyModifiers::NONE => {
+                    let count = count.unwrap_or_default() * 10 + digit;
+                    self.count = Some(count.min(MAX_COUNT));
+                    return Input::Pending;
+                }
+                _ => (),
+            }
+        }
+
+        self.keys.push(key);
+        match keymap.lookup(mode, &self.keys) {
+            Lookup::Action(action) => {
+                let (count, register) = (self.count, self.register);
+                self.clear();
+                Input::Command(action, count, register)
+            }
+            Lookup::Pending => Input::Pending,
+            Lookup::Unbound => {
+                self.clear();
+                Input::Unbound
+            }
+        }
+    }
+
+    /// Returns the count typed so far, if it is the only thing that has been typed
+    pub fn lone_count(&self) -> Option<usize> {
+        match self.keys.is_empty() && self.register.is_none() && !self.naming_register {
+            true => self.count,
+            false => None,
+        }
+    }
+
+    /// Throws away the keys typed so far
+    pub fn clear(&mut self) {
+        self.count = None;
+        self.register = None;
+        self.naming_register = false;
+        self.keys.clear();
+    }
+}
+
+impl fmt::Display for PendingInput {
+    /// Writes the keys typed so far the way they were typed, e.g. `"a3d`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(register) = self.register {
+            write!(f, "\"{}", register)?;
+        }
+        if self.naming_register {
+            write!(f, "\"")?;
+        }
+        if let Some(count) = self.count {
+            write!(f, "{}", count)?;
+        }
+        for key in &self.keys {
+            write!(f, "{}", key)?;
+        }
+        Ok(())
+    }
+}
+
+/// The keys that carry out each action
+pub struct Keymap {
+    bindings: Vec<(Vec<Key>, Action)>,
+    visual_bindings: Vec<(Vec<Key>, Action)>,
+}
+
+impl Keymap {
+    /// Replaces the keys bound to an action
+    ///
+    /// # Arguments
+    ///
+    /// * `mode`   - The mode the keys are used in
+    /// * `action` - The action to bind the keys to
+    /// * `keys`   - Each sequence of keys that should carry out the action
+    pub fn rebind(&mut self, mode: Mode, action: Action, keys: Vec<Vec<Key>>) {
+        let bindings = match mode {
+            Mode::Normal => &mut self.bindings,
+            Mode::Visual => &mut self.visual_bindings,
+        };
+
+        // Keeping the action where it was, so the help menu keeps its order
+        let position = bindings
+            .iter()
+            .position(|(_, bound)| *bound == action)
+            .unwrap_or(bindings.len());
+        bindings.retain(|(binding, bound)| *bound != action && !keys.contains(binding));
+        let position = position.min(bindings.len());
+        bindings.splice(
+            position..position,
+            keys.into_iter().map(|keys| (keys, action)),
+        );
+    }
+
+    /// Returns every action that has keys bound to it along with the keys, in the order they
+    /// were bound
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The mode the keys are used in
+    pub fn actions(&self, mode: Mode) -> Vec<(Action, Vec<String>)> {
+        let bindings = match mode {
+            Mode::Normal => &self.bindings,
+            Mode::Visual => &self.visual_bindings,
+        };
+
+        let mut actions: Vec<(Action, Vec<String>)> = vec![];
+        for (keys, action) in bindings {
+            let keys: String = keys.iter().map(|key| key.to_string()).collect();
+            match actions.iter_mut().find(|(bound, _)| bound == action) {
+                Some((_, bound_keys)) => bound_keys.push(keys),
+                None => actions.push((*action, vec![keys])),
+            }
+        }
+        actions
+    }
+
+    /// Finds the action bound to the keys pressed
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The mode the app is in
+    /// * `keys` - The keys pressed so far
+    pub fn lookup(&self, mode: Mode, keys: &[Key]) -> Lookup {
+        let bindings = match mode {
+            Mode::Normal => &self.bindings,
+            Mode::Visual => &self.visual_bindings,
+        };
+        let mut pending = false;
+
+        for (binding, action) in bindings {
+            if binding.as_slice() == keys {
+                return Lookup::Action(*action);
+            }
+            if binding.starts_with(keys) {
+                pending = true;
+            }
+        }
+
+        match pending {
+            true => Lookup::Pending,
+            false => Lookup::Unbound,
+        }
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use Action::*;
+
+        let chars = |keys: &str| keys.chars().map(|c| Key::new(KeyCode::Char(c))).collect();
+        let bindings: Vec<(Vec<Key>, Action)> = vec![
+            (chars("j"), NextTask),
+            (vec![Key::new(KeyCode::Down)], NextTask),
+            (chars("k"), PrevTask),
+            (vec![Key::new(KeyCode::Up)], PrevTask),
+            (chars("l"), NextList),
+            (vec![Key::new(KeyCode::Right)], NextList),
+            (chars("h"), PrevList),
+            (vec![Key::new(KeyCode::Left)], PrevList),
+            (chars("gg"), GotoTop),
+            (chars("G"), GotoBottom),
+            (chars("gH"), GotoScreenTop),
+            (chars("gM"), GotoScreenMiddle),
+            (chars("gL"), GotoScreenBottom),
+            (chars("gt"), GotoList),
+            (chars("gT"), PrevList),
+            (chars("{"), MoveListLeft),
+            (chars("}"), MoveListRight),
+            (chars("m"), ManageLists),
+            (chars("b"), ToggleBoard),
+            (chars("V"), Visual),
+            (chars("L"), MoveTaskToNextList),
+            (chars("H"), MoveTaskToPrevList),
+            (chars("J"), MoveTaskDown),
+            (vec![Key::alt('j')], MoveTaskDown),
+            (chars("K"), MoveTaskUp),
+            (vec![Key::alt('k')], MoveTaskUp),
+            (chars(" "), ToggleTask),
+            (chars("o"), NewTask),
+            (chars("O"), NewList),
+            (chars("r"), RewordTask),
+            (chars("R"), RenameList),
+            (chars("dd"), DeleteTask),
+            (chars("dA"), DeleteAllTasks),
+            (chars("dc"), DeleteCompletedTasks),
+            (chars("dC"), DeleteCompletedTasksOnAllLists),
+            (chars("D"), DeleteList),
+            (chars("yy"), YankTask),
+            (chars("yA"), YankList),
+            (chars("p"), PasteBelow),
+            (chars("P"), PasteAbove),
+            (chars(">>"), IndentTask),
+            (chars("<<"), OutdentTask),
+            (chars("za"), ToggleFold),
+            (chars("zM"), FoldAll),
+            (chars("zR"), UnfoldAll),
+            (chars("t"), SetDueDate),
+            (chars("+"), RaisePriority),
+            (chars("-"), LowerPriority),
+            (chars("s"), SortList),
+            (chars("S"), SortAllLists),
+            (chars("f"), FilterList),
+            (chars("F"), FilterAllLists),
+            (chars("/"), Search),
+            (chars("n"), NextMatch),
+            (chars("N"), PrevMatch),
+            (chars("."), RepeatLastChange),
+            (chars("u"), Undo),
+            (vec![Key::ctrl('r')], Redo),
+            (chars("?"), Help),
+            (chars("q"), Quit),
+            (chars("Q"), QuitWithoutSaving),
+        ];
+
+        let visual_bindings = vec![
+            (chars("j"), NextTask),
+            (vec![Key::new(KeyCode::Down)], NextTask),
+            (chars("k"), PrevTask),
+            (vec![Key::new(KeyCode::Up)], PrevTask),
+            (chars("gg"), GotoTop),
+            (chars("G"), GotoBottom),
+            (chars("gH"), GotoScreenTop),
+            (chars("gM"), GotoScreenMiddle),
+            (chars("gL"), GotoScreenBottom),
+            (chars("V"), Visual),
+            (vec![Key::new(KeyCode::Esc)], Visual),
+            (chars("y"), YankSelection),
+            (chars("d"), DeleteSelection),
+            (chars(" "), ToggleSelection),
+            (chars("L"), MoveSelectionToNextList),
+            (chars("H"), MoveSelectionToPrevList),
+            (chars("p"), PasteOverSelection),
+            (chars("q"), Visual),
+        ];
+
+        Self {
+            bindings,
+            visual_bindings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Types each character of the text as a key, returning what the last key made up
+    ///
+    /// # Arguments
+    ///
+    /// * `pending` - The keys typed before
+    /// * `text`    - The keys to type
+    fn type_keys(pending: &mut PendingInput, text: &str) -> Input {
+        let keymap = Keymap::default();
+        let mut input = Input::Pending;
+        for c in text.chars() {
+            input = pending.push(&keymap, Mode::Normal, Key::new(KeyCode::Char(c)));
+        }
+        input
+    }
+
+    #[test]
+    fn a_count_is_passed_on_with_the_command() {
+        let mut pending = PendingInput::default();
+        assert_eq!(
+            type_keys(&mut pending, "12j"),
+            Input::Command(Action::NextTask, Some(12), None)
+        );
+        assert_eq!(
+            type_keys(&mut pending, "j"),
+            Input::Command(Action::NextTask, None, None)
+        );
+    }
+
+    #[test]
+    fn a_count_cant_start_with_zero_or_be_too_big() {
+        let mut pending = PendingInput::default();
+        assert_eq!(type_keys(&mut pending, "0"), Input::Unbound);
+        assert_eq!(
+            type_keys(&mut pending, "10G"),
+            Input::Command(Action::GotoBottom, Some(10), None)
+        );
+        assert_eq!(
+            type_keys(&mut pending, "123456k"),
+            Input::Command(Action::PrevTask, Some(MAX_COUNT), None)
+        );
+    }
+
+    #[test]
+    fn a_number_typed_on_its_own_is_a_lone_count() {
+        let mut pending = PendingInput::default();
+        assert_eq!(type_keys(&mut pending, "3"), Input::Pending);
+        assert_eq!(pending.lone_count(), Some(3));
+
+        type_keys(&mut pending, "g");
+        assert_eq!(pending.lone_count(), None);
+        assert_eq!(pending.to_string(), "3g");
+    }
+
+    #[test]
+    fn keys_that_start_a_binding_wait_for_the_rest_of_it() {
+        let mut pending = PendingInput::default();
+        assert_eq!(type_keys(&mut pending, "g"), Input::Pending);
+        assert_eq!(
+            type_keys(&mut pending, "g"),
+            Input::Command(Action::GotoTop, None, None)
+        );
+        assert_eq!(
+            type_keys(&mut pending, "5gg"),
+            Input::Command(Action::GotoTop, Some(5), None)
+        );
+        assert_eq!(type_keys(&mut pending, "gx"), Input::Unbound);
+        assert_eq!(pending.to_string(), "");
+    }
+
+    #[test]
+    fn a_register_can_be_named_before_the_count() {
+        let mut pending = PendingInput::default();
+        assert_eq!(type_keys(&mut pending, "\"a2"), Input::Pending);
+        assert_eq!(pending.to_string(), "\"a2");
+        assert_eq!(
+            type_keys(&mut pending, "yy"),
+            Input::Command(Action::YankTask, Some(2), Some('a'))
+        );
+        assert_eq!(
+            type_keys(&mut pending, "\"\"p"),
+            Input::Command(Action::PasteBelow, None, None)
+        );
+    }
+
+    #[test]
+    fn keys_are_parsed_the_way_vim_writes_them() {
+        let char = |c| Key::new(KeyCode::Char(c));
+
+        assert_eq!(parse_keys("dd"), Ok(vec![char('d'), char('d')]));
+        assert_eq!(parse_keys("<C-r>"), Ok(vec![Key::ctrl('r')]));
+        assert_eq!(parse_keys("<A-J>"), Ok(vec![Key::alt('J')]));
+        assert_eq!(parse_keys("z<Space>"), Ok(vec![char('z'), char(' ')]));
+        assert_eq!(
+            parse_keys("<Down><esc>"),
+            Ok(vec![Key::new(KeyCode::Down), Key::new(KeyCode::Esc)])
+        );
+    }
+
+    #[test]
+    fn a_less_than_sign_that_doesnt_start_a_key_name_is_a_key() {
+        let char = |c| Key::new(KeyCode::Char(c));
+
+        assert_eq!(parse_keys("<<"), Ok(vec![char('<'), char('<')]));
+        assert_eq!(parse_keys("<lt>"), Ok(vec![char('<')]));
+        assert_eq!(parse_keys("<>"), Ok(vec![char('<'), char('>')]));
+        assert_eq!(parse_keys("<<C-r>"), Ok(vec![char('<'), Key::ctrl('r')]));
+    }
+
+    #[test]
+    fn keys_that_cant_be_parsed_are_reported() {
+        assert_eq!(
+            parse_keys("g<Nope>"),
+            Err("unknown key <Nope> in \"g<Nope>\"".to_string())
+        );
+        assert_eq!(parse_keys(""), Err("keys can't be empty".to_string()));
+    }
+
+    #[test]
+    fn keys_are_shown_the_way_they_are_written() {
+        let keys = parse_keys("g<C-r><A-Down><Space><lt>").unwrap();
+        let shown: String = keys.iter().map(|key| key.to_string()).collect();
+
+        assert_eq!(shown, "g<C-r><A-Down><Space><");
+    }
+
+    #[test]
+    fn rebinding_keys_unbinds_them_from_other_actions() {
+        let mut keymap = Keymap::default();
+        keymap.rebind(
+            Mode::Normal,
+            Action::NextTask,
+            vec![parse_keys("k").unwrap()],
+        );
+
+        let bound = |keymap: &Keymap, action| {
+            keymap
+                .actions(Mode::Normal)
+                .into_iter()
+                .find(|(bound, _)| *bound == action)
+                .map(|(_, keys)| keys)
+        };
+        assert_eq!(
+            bound(&keymap, Action::NextTask),
+            Some(vec!["k".to_string()])
+        );
+        assert_eq!(
+            bound(&keymap, Action::PrevTask),
+            Some(vec!["<Up>".to_string()])
+        );
+
+        keymap.rebind(Mode::Normal, Action::PrevTask, vec![]);
+        assert_eq!(bound(&keymap, Action::PrevTask), None);
+        assert_eq!(keymap.actions(Mode::Normal)[0].0, Action::NextTask);
+    }
+
+    #[test]
+    fn keys_that_arent_registers_are_thrown_away() {
+        let mut pending = PendingInput::default();
+        assert_eq!(type_keys(&mut pending, "\"!"), Input::Unbound);
+        assert_eq!(
+            type_keys(&mut pending, "p"),
+            Input::Command(Action::PasteBelow, None, None)
+        );
+    }
+}
@@ -0,0 +1,303 @@
+use crate::app::task::{List, Task};
+
+/// The result of merging the changes made to the lists in the app with the ones made to the task
+/// file outside of it
+pub struct Merge {
+    pub lists: Vec<List>,
+    /// How many tasks were changed differently in both, in which case the change made in the app
+    /// is kept
+    pub conflicts: usize,
+}
+
+/// Merges the changes made to the task file outside the app into the lists in the app. What each
+/// side changed is worked out by comparing it to the lists as they were when the file was last
+/// read or saved. Lists are matched by name and tasks by their text, so rewording a task counts as
+/// deleting it and adding a new one. A change to a list or task wins over deleting it
+///
+/// # Arguments
+///
+/// * `base`   - The lists as they were when the file was last read or saved
+/// * `ours`   - The lists in the app
+/// * `theirs` - The lists in the file now
+pub fn merge(base: &[List], ours: &[List], theirs: &[List]) -> Merge {
+    let mut conflicts = 0;
+    let mut lists = vec![];
+
+    for list in ours {
+        match (find_list(base, list), find_list(theirs, list)) {
+            (Some(base), Some(theirs)) => {
+                lists.push(merge_list(base, list, theirs, &mut conflicts))
+            }
+            (None, Some(theirs)) => {
+                let base = list.with_tasks(vec![]);
+                lists.push(merge_list(&base, list, theirs, &mut conflicts))
+            }
+            // Deleted outside the app, unless it was changed in it
+            (Some(base), None) if base == list => (),
+            _ => lists.push(list.clone()),
+        }
+    }
+
+    // Lists that were added outside the app, or deleted in it but changed outside of it
+    for (i, list) in theirs.iter().enumerate() {
+        if find_list(ours, list).is_some() || find_list(base, list) == Some(list) {
+            continue;
+        }
+
+        let position = theirs[..i]
+            .iter()
+            .rev()
+            .find_map(|previous| lists.iter().position(|l| l.name() == previous.name()))
+            .map_or(0, |position| position + 1);
+        lists.insert(position, list.clone());
+    }
+
+    Merge { lists, conflicts }
+}
+
+/// Finds the list with the same name as the given one
+///
+/// # Arguments
+///
+/// * `lists` - The lists to search
+/// * `list`  - The list to find the match of
+fn find_list<'a>(lists: &'a [List], list: &List) -> Option<&'a List> {
+    lists.iter().find(|l| l.name() == list.name())
+}
+
+/// Merges the changes made to a list in the app and outside of it
+///
+/// # Arguments
+///
+/// * `base`      - The list as it was when the file was last read or saved
+/// * `ours`      - The list in the app
+/// * `theirs`    - The list in the file now
+/// * `conflicts` - The number of conflicting changes, which is added to
+fn merge_list(base: &List, ours: &List, theirs: &List, conflicts: &mut usize) -> List {
+    let base_tasks: Vec<Task> = base.tasks_iter().cloned().collect();
+    let our_tasks: Vec<Task> = ours.tasks_iter().cloned().collect();
+    let their_tasks: Vec<Task> = theirs.tasks_iter().cloned().collect();
+    let tasks = merge_tasks(&base_tasks, &our_tasks, &their_tasks, conflicts);
+
+    // The heading and the notes under it are taken from outside the app unless they were changed
+    // in it
+    let unchanged = ours.heading() == base.heading() && ours.notes() == base.notes();
+    match unchanged {
+        true => theirs.with_tasks(tasks),
+        false => ours.with_tasks(tasks),
+    }
+}
+
+/// Identifies a task by its text, and by how many tasks before it have the same text
+type TaskKey = (String, usize);
+
+/// Pairs each task with its key
+///
+/// # Arguments
+///
+/// * `tasks` - The tasks to pair
+fn keyed(tasks: &[Task]) -> Vec<(TaskKey, Task)> {
+    let mut keyed: Vec<(TaskKey, Task)> = vec![];
+
+    for task in tasks {
+        let text = task.text();
+        let n = keyed.iter().filter(|((t, _), _)| *t == text).count();
+        keyed.push(((text, n), task.clone()));
+    }
+    keyed
+}
+
+/// Merges the changes made to the tasks of a list in the app and outside of it
+///
+/// # Arguments
+///
+/// * `base`      - The tasks as they were when the file was last read or saved
+/// * `ours`      - The tasks in the app
+/// * `theirs`    - The tasks in the file now
+/// * `conflicts` - The number of conflicting changes, which is added to
+fn merge_tasks(base: &[Task], ours: &[Task], theirs: &[Task], conflicts: &mut usize) -> Vec<Task> {
+    let base = keyed(base);
+    let theirs = keyed(theirs);
+    let mut merged = keyed(ours);
+
+    let find = |tasks: &[(TaskKey, Task)], key: &TaskKey| tasks.iter().position(|(k, _)| k == key);
+
+    // Tasks that were changed or deleted outside the app
+    let mut deleted = vec![];
+    for (key, base_task) in &base {
+        let (i, ours) = match find(&merged, key) {
+            Some(i) => (i, &merged[i].1),
+            None => continue,
+        };
+
+        match find(&theirs, key).map(|j| &theirs[j].1) {
+            None if ours == base_task => deleted.push(key.clone()),
+            Some(theirs) if theirs != base_task && ours == base_task => {
+                merged[i].1 = theirs.clone()
+            }
+            Some(theirs) if theirs != base_task && ours != theirs => *conflicts += 1,
+            _ => (),
+        }
+    }
+    merged.retain(|(key, _)| !deleted.contains(key));
+
+    // Tasks that were added outside the app, or deleted in it but changed outside of it
+    for (i, (key, task)) in theirs.iter().enumerate() {
+        let base_task = find(&base, key).map(|j| &base[j].1);
+        if find(&merged, key).is_some() || base_task == Some(task) {
+            continue;
+        }
+
+        let position = theirs[..i]
+            .iter()
+            .rev()
+            .find_map(|(previous, _)| find(&merged, previous))
+            .map_or(0, |position| position + 1);
+        merged.insert(position, (key.clone(), task.clone()));
+    }
+
+    // A subtask can end up without its parent, so it is moved out to sit under the task above it
+    let mut tasks: Vec<Task> = merged.into_iter().map(|(_, task)| task).collect();
+    let mut max_depth = 0;
+    for task in &mut tasks {
+        task.set_depth(task.depth().min(max_depth));
+        max_depth = task.depth() + 1;
+    }
+    tasks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a task nested under the given number of tasks
+    fn task(description: &str, depth: usize) -> Task {
+        let mut task = Task::new(description);
+        task.set_depth(depth);
+        task
+    }
+
+    /// Creates a completed task at the top level
+    fn completed(description: &str) -> Task {
+        let mut task = Task::new(description);
+        task.toggle_status();
+        task
+    }
+
+    /// Merges tasks, returning their descriptions along with the number of conflicts
+    fn merge_texts(base: &[Task], ours: &[Task], theirs: &[Task]) -> (Vec<String>, usize) {
+        let mut conflicts = 0;
+        let tasks = merge_tasks(base, ours, theirs, &mut conflicts);
+        (tasks.iter().map(Task::text).collect(), conflicts)
+    }
+
+    #[test]
+    fn task_reworded_outside_replaces_the_old_one() {
+        let base = [task("a", 0), task("b", 0)];
+        let theirs = [task("a", 0), task("b reworded", 0)];
+
+        let (tasks, conflicts) = merge_texts(&base, &base, &theirs);
+        assert_eq!(tasks, ["a", "b reworded"]);
+        assert_eq!(conflicts, 0);
+    }
+
+    #[test]
+    fn task_reworded_on_both_sides_keeps_both_versions() {
+        let base = [task("a", 0), task("b", 0)];
+        let ours = [task("a", 0), task("b ours", 0)];
+        let theirs = [task("a", 0), task("b theirs", 0)];
+
+        let (tasks, _) = merge_texts(&base, &ours, &theirs);
+        assert_eq!(tasks, ["a", "b theirs", "b ours"]);
+    }
+
+    #[test]
+    fn task_changed_in_app_survives_being_deleted_outside() {
+        let base = [task("a", 0), task("b", 0)];
+        let ours = [task("a", 0), completed("b")];
+        let theirs = [task("a", 0)];
+
+        let mut conflicts = 0;
+        let tasks = merge_tasks(&base, &ours, &theirs, &mut conflicts);
+        assert_eq!(tasks, ours);
+    }
+
+    #[test]
+    fn task_changed_outside_survives_being_deleted_in_app() {
+        let base = [task("a", 0), task("b", 0)];
+        let ours = [task("a", 0)];
+        let theirs = [task("a", 0), completed("b")];
+
+        let mut conflicts = 0;
+        let tasks = merge_tasks(&base, &ours, &theirs, &mut conflicts);
+        assert_eq!(tasks, theirs);
+    }
+
+    #[test]
+    fn unchanged_task_deleted_outside_is_deleted() {
+        let base = [task("a", 0), task("b", 0)];
+        let theirs = [task("b", 0)];
+
+        let (tasks, _) = merge_texts(&base, &base, &theirs);
+        assert_eq!(tasks, ["b"]);
+    }
+
+    #[test]
+    fn task_changed_on_both_sides_keeps_ours_and_counts_a_conflict() {
+        let base = [task("a", 0), task("b", 0)];
+        let ours = [task("a", 0), completed("b")];
+        let theirs = [task("a", 0), task("b", 1)];
+
+        let mut conflicts = 0;
+        let tasks = merge_tasks(&base, &ours, &theirs, &mut conflicts);
+        assert_eq!(tasks, ours);
+        assert_eq!(conflicts, 1);
+    }
+
+    #[test]
+    fn task_added_outside_goes_after_the_task_before_it() {
+        let base = [task("a", 0), task("c", 0)];
+        let ours = [task("a", 0), task("c", 0), task("d", 0)];
+        let theirs = [task("a", 0), task("b", 0), task("c", 0)];
+
+        let (tasks, _) = merge_texts(&base, &ours, &theirs);
+        assert_eq!(tasks, ["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn orphaned_subtask_is_moved_out() {
+        let mut changed_sub = completed("sub");
+        changed_sub.set_depth(1);
+        let base = [task("parent", 0), task("sub", 1)];
+        let ours = [task("parent", 0), changed_sub];
+        let theirs = [];
+
+        let mut conflicts = 0;
+        let tasks = merge_tasks(&base, &ours, &theirs, &mut conflicts);
+        assert_eq!(tasks, [completed("sub")]);
+    }
+
+    #[test]
+    fn list_added_outside_goes_after_the_list_before_it() {
+        let base = [List::new("Todo").unwrap(), List::new("Done").unwrap()];
+        let theirs = [
+            List::new("Todo").unwrap(),
+            List::new("Doing").unwrap(),
+            List::new("Done").unwrap(),
+        ];
+
+        let merged = merge(&base, &base, &theirs);
+        let names: Vec<String> = merged.lists.iter().map(List::name).collect();
+        assert_eq!(names, ["Todo", "Doing", "Done"]);
+    }
+
+    #[test]
+    fn unchanged_list_deleted_outside_is_deleted() {
+        let base = [List::new("Todo").unwrap(), List::new("Done").unwrap()];
+        let theirs = [List::new("Todo").unwrap()];
+
+        let merged = merge(&base, &base, &theirs);
+        let names: Vec<String> = merged.lists.iter().map(List::name).collect();
+        assert_eq!(names, ["Todo"]);
+    }
+}
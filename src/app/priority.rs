@@ -0,0 +1,210 @@
+use crate::app::theme::theme;
+
+use colored::{ColoredString, Colorize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// How important a task is, `A` being the most important
+pub enum Priority {
+    A,
+    B,
+    C,
+}
+
+impl Priority {
+    /// Returns the next most important priority, or None if this is already the most important
+    pub fn raised(self) -> Option<Priority> {
+        match self {
+            Priority::A => None,
+            Priority::B => Some(Priority::A),
+            Priority::C => Some(Priority::B),
+        }
+    }
+
+    /// Returns the next least important priority, or None if this is already the least important
+    pub fn lowered(self) -> Option<Priority> {
+        match self {
+            Priority::A => Some(Priority::B),
+            Priority::B => Some(Priority::C),
+            Priority::C => None,
+        }
+    }
+
+    /// Returns the exclamation marks the priority is written as, more being more important
+    fn exclamation_marks(&self) -> &'static str {
+        match self {
+            Priority::A => "!!!",
+            Priority::B => "!!",
+            Priority::C => "!",
+        }
+    }
+
+    /// Returns the priority written as the given exclamation marks, if they are one
+    ///
+    /// # Arguments
+    ///
+    /// * `word` - The word to read
+    fn from_exclamation_marks(word: &str) -> Option<Priority> {
+        [Priority::A, Priority::B, Priority::C]
+            .into_iter()
+            .find(|priority| priority.exclamation_marks() == word)
+    }
+
+    /// Returns the letter of the priority
+    fn letter(&self) -> char {
+        match self {
+            Priority::A => 'A',
+            Priority::B => 'B',
+            Priority::C => 'C',
+        }
+    }
+
+    /// Returns the priority as it should be shown in the app
+    pub fn coloured(&self) -> ColoredString {
+        let marker = format!("({})", self.letter());
+        match self {
+            Priority::A => marker.color(theme().priority_a).bold(),
+            Priority::B => marker.color(theme().priority_b),
+            Priority::C => marker.color(theme().priority_c),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// The way a priority is written in the task file
+pub enum PriorityFormat {
+    /// `(A) Task`, as used by todo.txt
+    #[default]
+    Letter,
+    /// `Task !!!`, where more exclamation marks are more important
+    Exclamation,
+    /// `!!! Task`, the same but at the start of the task
+    LeadingExclamation,
+}
+
+impl PriorityFormat {
+    /// Adds the given priority to a task's description in this format
+    ///
+    /// # Arguments
+    ///
+    /// * `priority`    - The priority to add
+    /// * `description` - The description of the task
+    pub fn apply(&self, priority: Priority, description: &str) -> String {
+        match self {
+            PriorityFormat::Letter => format!("({}) {}", priority.letter(), description),
+            PriorityFormat::Exclamation => {
+                format!("{} {}", description, priority.exclamation_marks())
+            }
+            PriorityFormat::LeadingExclamation => {
+                format!("{} {}", priority.exclamation_marks(), description)
+            }
+        }
+    }
+}
+
+/// Finds a priority written in a task description, either as `(A)`, `(B)` or `(C)` at the start
+/// of it, or as a word made of one to three exclamation marks at the end or start of it.
+/// Exclamation marks in the middle of the description are left in it
+///
+/// # Arguments
+///
+/// * `text` - The text of the task
+///
+/// # Returns
+///
+/// The text with the priority removed, along with the priority and the format it was written in
+/// if one was found
+pub fn extract_priority(text: &str) -> (String, Option<(Priority, PriorityFormat)>) {
+    let letter = match text.split(' ').next() {
+        Some("(A)") => Some(Priority::A),
+        Some("(B)") => Some(Priority::B),
+        Some("(C)") => Some(Priority::C),
+        _ => None,
+    };
+
+    if let Some(priority) = letter {
+        let remaining = text
+            .split_once(' ')
+            .map_or("", |(_, rest)| rest)
+            .trim()
+            .to_string();
+        if !remaining.is_empty() {
+            return (remaining, Some((priority, PriorityFormat::Letter)));
+        }
+    }
+
+    let words: Vec<&str> = text.trim().split(' ').collect();
+    let trailing = words
+        .last()
+        .and_then(|word| Priority::from_exclamation_marks(word));
+    let leading = words
+        .first()
+        .and_then(|word| Priority::from_exclamation_marks(word));
+    let (priority, remaining, format) = match (trailing, leading) {
+        (Some(priority), _) => (
+            priority,
+            &words[..words.len() - 1],
+            PriorityFormat::Exclamation,
+        ),
+        (None, Some(priority)) => (priority, &words[1..], PriorityFormat::LeadingExclamation),
+        (None, None) => return (text.to_string(), None),
+    };
+
+    let remaining = remaining.join(" ");
+    match remaining.trim().is_empty() {
+        true => (text.to_string(), None),
+        false => (remaining.trim().to_string(), Some((priority, format))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letters_are_read_at_the_start() {
+        assert_eq!(
+            extract_priority("(B) Call the bank"),
+            (
+                "Call the bank".to_string(),
+                Some((Priority::B, PriorityFormat::Letter))
+            )
+        );
+    }
+
+    #[test]
+    fn exclamation_marks_are_read_at_the_end_or_start() {
+        assert_eq!(
+            extract_priority("Call the bank !!"),
+            (
+                "Call the bank".to_string(),
+                Some((Priority::B, PriorityFormat::Exclamation))
+            )
+        );
+        assert_eq!(
+            extract_priority("! Call the bank"),
+            (
+                "Call the bank".to_string(),
+                Some((Priority::C, PriorityFormat::LeadingExclamation))
+            )
+        );
+    }
+
+    #[test]
+    fn exclamation_marks_in_the_middle_are_left_alone() {
+        let text = "Shout ! at the ! bank";
+        assert_eq!(extract_priority(text), (text.to_string(), None));
+    }
+
+    #[test]
+    fn priorities_are_written_where_they_were_read() {
+        for text in [
+            "(A) Call the bank",
+            "Call the bank !!!",
+            "!!! Call the bank",
+        ] {
+            let (description, priority) = extract_priority(text);
+            let (priority, format) = priority.unwrap();
+            assert_eq!(format.apply(priority, &description), text);
+        }
+    }
+}
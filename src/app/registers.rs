@@ -0,0 +1,188 @@
+use crate::app::error::TasksError;
+use crate::app::serialisation::{tasks_from_markdown, tasks_to_markdown};
+use crate::app::task::Task;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use crossterm::{execute, style::Print};
+use std::collections::HashMap;
+use std::io::{self, stdout, Write};
+use std::process::{Command, Stdio};
+
+/// The register that reaches the system clipboard
+pub const SYSTEM_REGISTER: char = '+';
+
+/// The commands tried in order to copy text to the system clipboard
+const COPY_COMMANDS: [(&str, &[&str]); 4] = [
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("pbcopy", &[]),
+];
+
+/// The commands tried in order to paste text from the system clipboard
+const PASTE_COMMANDS: [(&str, &[&str]); 4] = [
+    ("wl-paste", &["--no-newline"]),
+    ("xclip", &["-selection", "clipboard", "-o"]),
+    ("xsel", &["--clipboard", "--output"]),
+    ("pbpaste", &[]),
+];
+
+/// Returns whether tasks can be yanked into and pasted from the given register. These are `a` to
+/// `z`, `A` to `Z` to add to the end of the lowercase register, `+` for the system clipboard and
+/// `"` for the unnamed register
+///
+/// # Arguments
+///
+/// * `register` - The name of the register
+pub fn is_register(register: char) -> bool {
+    register.is_ascii_alphabetic() || register == SYSTEM_REGISTER || register == '"'
+}
+
+/// Where yanked tasks are kept, like vim's registers
+#[derive(Debug, Default)]
+pub struct Registers {
+    /// The tasks yanked or deleted last, which are pasted when no register is given
+    unnamed: Vec<Task>,
+    named: HashMap<char, Vec<Task>>,
+}
+
+impl Registers {
+    /// Stores tasks in the given register. The unnamed register always gets the tasks too, so
+    /// they can be pasted without naming the register
+    ///
+    /// # Arguments
+    ///
+    /// * `register` - The register to store the tasks in, or None for only the unnamed register
+    /// * `tasks`    - The tasks to store
+    ///
+    /// # Returns
+    ///
+    /// Why the tasks couldn't be copied to the system clipboard, if they couldn't. The unnamed
+    /// register gets them either way
+    pub fn yank(&mut self, register: Option<char>, tasks: Vec<Task>) -> Result<(), TasksError> {
+        let mut result = Ok(());
+        let tasks = match register {
+            Some(SYSTEM_REGISTER) => {
+                result = copy_to_system_clipboard(&tasks_to_markdown(&tasks));
+                tasks
+            }
+            Some(register) if register.is_ascii_uppercase() => {
+                let stored = self.named.entry(register.to_ascii_lowercase()).or_default();
+                stored.extend(tasks);
+                stored.clone()
+            }
+            Some(register) if register.is_ascii_lowercase() => {
+                self.named.insert(register, tasks.clone());
+                tasks
+            }
+            _ => tasks,
+        };
+
+        self.unnamed = tasks;
+        result
+    }
+
+    /// Returns the tasks yanked or deleted last, which are pasted when no register is given
+    pub fn last(&self) -> &[Task] {
+        &self.unnamed
+    }
+
+    /// Returns the tasks stored in the given register, reading them from the system clipboard
+    /// for the `+` register
+    ///
+    /// # Arguments
+    ///
+    /// * `register` - The register to get the tasks from, or None for the unnamed register
+    pub fn get(&self, register: Option<char>) -> Vec<Task> {
+        match register {
+            Some(SYSTEM_REGISTER) => paste_from_system_clipboard()
+                .map(|text| tasks_from_markdown(&text))
+                .unwrap_or_default(),
+            Some(register) if register.is_ascii_alphabetic() => self
+                .named
+                .get(&register.to_ascii_lowercase())
+                .cloned()
+                .unwrap_or_default(),
+            _ => self.unnamed.clone(),
+        }
+    }
+}
+
+/// Copies text to the system clipboard. The OSC 52 escape sequence is written so that it works
+/// over SSH in terminals that support it, and a local clipboard command is also run if there is
+/// one, for terminals that don't. Commands that aren't installed are skipped
+///
+/// # Arguments
+///
+/// * `text` - The text to copy
+///
+/// # Returns
+///
+/// Why the text couldn't be copied, if the escape sequence couldn't be written and no clipboard
+/// command worked either. A failed command, such as xclip when there is no display to connect to,
+/// isn't reported once the escape sequence has been written, as the terminal may still have copied
+/// the text
+fn copy_to_system_clipboard(text: &str) -> Result<(), TasksError> {
+    let osc = execute!(
+        stdout(),
+        Print(format!("\x1b]52;c;{}\x07", STANDARD.encode(text)))
+    );
+
+    let mut error = None;
+    for (program, args) in COPY_COMMANDS {
+        let child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(_) => continue,
+        };
+
+        // The command is waited for even if writing to it failed, so it doesn't linger
+        let written = match child.stdin.take() {
+            Some(mut stdin) => stdin.write_all(text.as_bytes()),
+            None => Ok(()),
+        };
+        let status = child.wait();
+        match (written, status) {
+            (Ok(()), Ok(status)) if status.success() => return Ok(()),
+            (Err(e), _) | (_, Err(e)) => error = Some(format!("{}: {}", program, e)),
+            (Ok(()), Ok(status)) => error = Some(format!("{} failed with {}", program, status)),
+        }
+    }
+
+    let error = match (osc, error) {
+        (Ok(()), _) => return Ok(()),
+        (Err(_), Some(error)) => error,
+        (Err(e), None) => format!("the terminal couldn't be written to: {}", e),
+    };
+    Err(TasksError::Io(io::Error::other(format!(
+        "Couldn't copy to the system clipboard, {}",
+        error
+    ))))
+}
+
+/// Reads text from the system clipboard with the first local clipboard command that works. Few
+/// terminals allow reading the clipboard with OSC 52, so it isn't used here
+///
+/// # Returns
+///
+/// The text in the clipboard, or None if no command could read it
+fn paste_from_system_clipboard() -> Option<String> {
+    PASTE_COMMANDS.iter().find_map(|(program, args)| {
+        let output = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+
+        match output.status.success() {
+            true => String::from_utf8(output.stdout).ok(),
+            false => None,
+        }
+    })
+}
@@ -1,82 +1,487 @@
-use crate::app::task::List;
-use std::fs::File;
-use std::io::{
-    prelude::{Read, Write},
-    Result,
-};
+use crate::app::error::TasksError;
+use crate::app::task::{List, Task};
+use chrono::Local;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::prelude::{Read, Write};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
 
-/// The filename that the app should serialise and deserialise from
-const FILE_NAME: &str = ".tasks.md";
+/// The filename that the app should serialise and deserialise from if no other file is given
+pub const DEFAULT_FILE_NAME: &str = ".tasks.md";
 
-/// Writes the given vector of list structs to the .tasks.md file
+/// The indentation written before a subtask for each level it is nested
+const INDENT: &str = "  ";
+
+/// The deepest markdown heading that names a list, deeper headings are kept as notes
+const MAX_LIST_HEADING: usize = 2;
+
+/// The contents of a task file
+pub struct Document {
+    /// The lines before the first list, written back as they were read
+    pub preamble: Vec<String>,
+    pub lists: Vec<List>,
+}
+
+/// Writes the given document to the given file. The lines that aren't tasks, including blank
+/// lines, are written back where they were read from, and lists added since the file was read
+/// are separated from what is before them by a blank line
+///
+/// The document is written to a temporary file next to the file, which then replaces it, so the
+/// file is never left half written if writing fails part way through. What the file contained
+/// before is kept as a backup
 ///
 /// # Arguments
 ///
-/// * `lists` - The vector of list's to serialise
-pub fn serialise(lists: Vec<List>) -> Result<()> {
-    let mut file = File::create(FILE_NAME)?;
+/// * `path`     - The path of the file to write to
+/// * `document` - The document to serialise
+/// * `backups`  - How many previous versions of the file to keep
+pub fn serialise<P: AsRef<Path>>(
+    path: P,
+    document: &Document,
+    backups: usize,
+) -> Result<(), TasksError> {
+    let path = resolve(path.as_ref());
+    let temp = temp_path(&path);
 
-    for list in lists {
-        writeln!(file, "{}", list.name())?;
+    let file = File::create(&temp)?;
+    // The temporary file is removed if the save fails, so that it isn't offered as a save that
+    // didn't finish the next time the app starts
+    let result = write_document(file, document).and_then(|()| {
+        if let Ok(metadata) = fs::metadata(&path) {
+            fs::set_permissions(&temp, metadata.permissions())?;
+        }
+        back_up(&path, backups)?;
+        fs::rename(&temp, &path)?;
+        Ok(())
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+        return result;
+    }
+
+    sync_parent(&path);
+    Ok(())
+}
+
+/// Writes the given document to a file and makes sure it reaches the disk
+///
+/// # Arguments
+///
+/// * `file`     - The file to write to
+/// * `document` - The document to write
+fn write_document(file: File, document: &Document) -> Result<(), TasksError> {
+    let mut file = BufWriter::new(file);
+
+    for line in &document.preamble {
+        writeln!(file, "{}", line)?;
+    }
+
+    let mut is_start = document.preamble.is_empty();
+    for list in &document.lists {
+        // Lists that weren't read from the file are separated from what is before them by a
+        // single blank line
+        match list.blank_lines_before() {
+            Some(lines) => {
+                for line in lines {
+                    writeln!(file, "{}", line)?;
+                }
+            }
+            None if !is_start => writeln!(file)?,
+            None => (),
+        }
+        is_start = false;
+
+        match list.heading() {
+            Some(level) => writeln!(file, "{} {}", "#".repeat(level), list.name())?,
+            None => writeln!(file, "{}", list.name())?,
+        }
+        for note in list.notes() {
+            writeln!(file, "{}", note)?;
+        }
         for task in list.tasks_iter() {
-            writeln!(
-                file,
-                "- [{}] {}",
-                match task.status() {
-                    true => "x",
-                    false => " ",
-                },
-                task.description()
-            )?;
+            writeln!(file, "{}", task_line(task, task.depth()))?;
+            for note in task.notes() {
+                writeln!(file, "{}", note)?;
+            }
         }
-        writeln!(file)?;
     }
 
     file.flush()?;
+    file.get_ref().sync_all()?;
+    Ok(())
+}
+
+/// Returns the path of the temporary file the task file is written to before it replaces the
+/// task file
+///
+/// # Arguments
+///
+/// * `path` - The path of the task file
+pub fn temp_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    with_suffix(&resolve(path.as_ref()), ".tmp")
+}
+
+/// Returns the path of the file that unsaved changes are written to if the app is closed
+/// unexpectedly
+///
+/// # Arguments
+///
+/// * `path` - The path of the task file
+pub fn recovery_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    with_suffix(&resolve(path.as_ref()), ".recovery")
+}
+
+/// Replaces the task file with a file left over from when the app last exited, such as the
+/// temporary file from a save that didn't finish, keeping what the task file contained as a
+/// backup
+///
+/// # Arguments
+///
+/// * `path`     - The path of the task file
+/// * `leftover` - The path of the file to replace it with
+/// * `backups`  - How many previous versions of the file to keep
+pub fn recover_file<P: AsRef<Path>>(
+    path: P,
+    leftover: &Path,
+    backups: usize,
+) -> Result<(), TasksError> {
+    let path = resolve(path.as_ref());
+
+    back_up(&path, backups)?;
+    fs::rename(leftover, &path)?;
+    sync_parent(&path);
+    Ok(())
+}
+
+/// Moves a file left over from when the app last exited out of the way, by adding the time to
+/// its name, so that it is kept without being offered again
+///
+/// # Arguments
+///
+/// * `leftover` - The path of the left over file
+///
+/// # Returns
+///
+/// The path the file was moved to
+pub fn set_aside(leftover: &Path) -> Result<PathBuf, TasksError> {
+    let time = Local::now().format(".%Y%m%d-%H%M%S");
+    let aside = with_suffix(leftover, &time.to_string());
+    fs::rename(leftover, &aside)?;
+    Ok(aside)
+}
+
+/// Follows the path if it is a symlink, so that saving replaces the file it points to rather
+/// than the link
+///
+/// # Arguments
+///
+/// * `path` - The path of the task file
+fn resolve(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or(path.to_path_buf())
+}
+
+/// Copies the file to its backup, if it exists. With more than one backup, the older backups are
+/// numbered, and the oldest is removed once there are too many
+///
+/// # Arguments
+///
+/// * `path`    - The path of the file to back up
+/// * `backups` - How many backups to keep
+fn back_up(path: &Path, backups: usize) -> Result<(), TasksError> {
+    if backups == 0 || !path.exists() {
+        return Ok(());
+    }
+
+    for n in (1..backups).rev() {
+        let newer = backup_path(path, n - 1);
+        if newer.exists() {
+            fs::rename(newer, backup_path(path, n))?;
+        }
+    }
+    fs::copy(path, backup_path(path, 0))?;
     Ok(())
 }
 
-/// Reads the .tasks.md file and returns a vector of list's
+/// Returns the path of a backup of the file, which is `.tasks.md.bak` for the newest, then
+/// `.tasks.md.bak.1`, `.tasks.md.bak.2` and so on
+///
+/// # Arguments
+///
+/// * `path` - The path of the file
+/// * `n`    - How many backups are newer than this one
+fn backup_path(path: &Path, n: usize) -> PathBuf {
+    match n {
+        0 => with_suffix(path, ".bak"),
+        n => with_suffix(path, &format!(".bak.{}", n)),
+    }
+}
+
+/// Returns the path with some text added to the end of its file name
+///
+/// # Arguments
+///
+/// * `path`   - The path to add to
+/// * `suffix` - The text to add
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(OsStr::new(suffix));
+    PathBuf::from(name)
+}
+
+/// Makes sure a file being renamed is written to the disk, by syncing the directory it is in.
+/// Not every platform can sync a directory, so failing to is ignored
+///
+/// # Arguments
+///
+/// * `path` - The path of the file
+fn sync_parent(path: &Path) {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    if let Ok(dir) = File::open(parent) {
+        let _ = dir.sync_all();
+    }
+}
+
+/// Reads the given file and returns the document it contains
 ///
 /// The following guidelines are followed when reading the file
-/// - Empty lines are skipped
+/// - `#` and `##` headings are the names of lists
 /// - Lines beginning with '- [x] ' or '- [ ] ' are added as task to the current lists
-/// - Every line that is not empty and does not meet the previous criteria is treated as a new list
+/// - Task lines that are indented further than the task above them are subtasks of that task
+/// - Every other line is kept as a note after the task or heading above it, and written back as
+///   it was. Blank lines before a heading are kept with the list it names instead. This includes checkboxes other than '[ ]' and '[x]', such as
+///   '- [-] ', and tasks before the first list, which are kept in the preamble
+///
+/// Older task files without any headings are read the same way, except that every line that
+/// isn't empty or a task is treated as the name of a new list
+///
+/// # Arguments
+///
+/// * `path` - The path of the file to read from
 ///
 /// # Returns
 ///
-/// The deserialised data as a vector of `List` structs
-pub fn deserialise() -> Result<Vec<List>> {
-    let mut lists: Vec<List> = vec![];
+/// The deserialised document, `TasksError::Io` if the file couldn't be read, or
+/// `TasksError::Parse` if it isn't text
+pub fn deserialise<P: AsRef<Path>>(path: P) -> Result<Document, TasksError> {
+    let mut document = Document {
+        preamble: vec![],
+        lists: vec![],
+    };
 
-    let mut file = File::open(FILE_NAME)?;
+    let mut file = File::open(path)?;
 
-    let mut contents = String::new();
+    let mut bytes = vec![];
 
-    file.read_to_string(&mut contents)?;
+    file.read_to_end(&mut bytes)?;
+    let contents = String::from_utf8(bytes).map_err(|e| {
+        let valid = &e.as_bytes()[..e.utf8_error().valid_up_to()];
+        TasksError::Parse {
+            line: valid.iter().filter(|&&byte| byte == b'\n').count() + 1,
+            reason: "it isn't UTF-8 text".to_string(),
+        }
+    })?;
+
+    let has_headings = contents.lines().any(|line| list_heading(line).is_some());
+
+    // The indentation of each task the current task could be nested under
+    let mut indents: Vec<usize> = vec![];
+    // The lines read since the last task or list, which are added once it is known whether they
+    // are the blank lines before the next list
+    let mut notes: Vec<String> = vec![];
 
     for line in contents.lines() {
-        if line.is_empty() {
+        let name = match has_headings {
+            true => list_heading(line).map(|(level, name)| (Some(level), name)),
+            false if is_blank(line) || is_task_line(line) => None,
+            false => Some((None, line)),
+        };
+
+        if let Some((heading, name)) = name {
+            let blank = notes.iter().rev().take_while(|line| is_blank(line)).count();
+            let blank_lines = notes.split_off(notes.len() - blank);
+            add_notes(&mut document, notes);
+            notes = vec![];
+            indents.clear();
+
+            let mut list = List::new(name)?;
+            list.set_heading(heading);
+            list.set_blank_lines_before(blank_lines);
+            document.lists.push(list);
             continue;
         }
 
-        if line.len() > 5 && line[0..3].to_string() == "- [" {
-            if let Some(list) = lists.last_mut() {
-                list.add_task(line[6..].to_string());
-                if line[3..4].to_string() == "x" {
-                    list.toggle_task(list.length() - 1);
-                }
-            }
+        if let (true, Some(list)) = (is_task_line(line), document.lists.last_mut()) {
+            list.add_notes(notes);
+            notes = vec![];
+
+            let task = parse_task_line(line, &mut indents);
+            list.insert_task(list.length(), task)?;
             continue;
         }
 
-        lists.push(
-            List::new(line.to_string())
-                .expect("We have checked to see if this string is empty already"),
-        );
+        notes.push(line.to_string());
+    }
+    add_notes(&mut document, notes);
+
+    Ok(document)
+}
+
+/// Adds lines that aren't tasks after whatever was read last, which is the preamble of the
+/// document if no list has been read yet
+///
+/// # Arguments
+///
+/// * `document` - The document being read
+/// * `notes`    - The lines to add
+fn add_notes(document: &mut Document, notes: Vec<String>) {
+    match document.lists.last_mut() {
+        Some(list) => list.add_notes(notes),
+        None => document.preamble.extend(notes),
     }
+}
+
+/// Returns whether a line of a task file is blank, meaning it is empty or only whitespace
+///
+/// # Arguments
+///
+/// * `line` - The line to check
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+/// Returns the level and text of a line if it is a markdown heading that names a list
+///
+/// # Arguments
+///
+/// * `line` - The line to check
+fn list_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let name = line[level..].strip_prefix(' ')?.trim();
+
+    match (1..=MAX_LIST_HEADING).contains(&level) && !name.is_empty() {
+        true => Some((level, name)),
+        false => None,
+    }
+}
+
+/// Returns whether a line of a task file is a task, meaning it begins with the checkbox '- [ ] ',
+/// '- [x] ' or '- [X] ' after any indentation. Lines such as '- [a link](...)' or '- [-] ' aren't
+/// tasks
+///
+/// # Arguments
+///
+/// * `line` - The line to check
+fn is_task_line(line: &str) -> bool {
+    let graphemes: Vec<&str> = line.trim_start().graphemes(true).take(6).collect();
+    matches!(
+        graphemes.as_slice(),
+        ["-", " ", "[", " " | "x" | "X", "]", " "]
+    )
+}
+
+/// Parses a task line, working out its depth from how far it is indented compared to the task
+/// lines before it
+///
+/// # Arguments
+///
+/// * `line`    - The line to parse, which must be a task line
+/// * `indents` - The indentation of each task the task could be nested under, which is updated
+///   to include this task
+fn parse_task_line(line: &str, indents: &mut Vec<usize>) -> Task {
+    let mut graphemes = line.trim_start().graphemes(true);
+    let checkbox: String = graphemes.by_ref().skip(2).take(4).collect();
+    let completed = checkbox != "[ ] ";
+
+    let indent = indent_width(line);
+    while indents.last().is_some_and(|&last| last >= indent) {
+        indents.pop();
+    }
+
+    let mut task = Task::new(graphemes.as_str().to_string());
+    task.set_depth(indents.len());
+    if completed {
+        task.toggle_status();
+    }
+    indents.push(indent);
+    task
+}
+
+/// Formats a task as a line of a task file
+///
+/// # Arguments
+///
+/// * `task`  - The task to format
+/// * `depth` - How many levels the task should be indented
+fn task_line(task: &Task, depth: usize) -> String {
+    format!(
+        "{}- [{}] {}",
+        INDENT.repeat(depth),
+        match task.status() {
+            true => "x",
+            false => " ",
+        },
+        task.text()
+    )
+}
+
+/// Formats tasks as a markdown checklist, the same as they are written in the task file. The
+/// least nested tasks aren't indented
+///
+/// # Arguments
+///
+/// * `tasks` - The tasks to format
+pub fn tasks_to_markdown(tasks: &[Task]) -> String {
+    let min_depth = tasks.iter().map(|task| task.depth()).min().unwrap_or(0);
+
+    tasks
+        .iter()
+        .map(|task| format!("{}\n", task_line(task, task.depth() - min_depth)))
+        .collect()
+}
+
+/// Reads tasks from text, such as a markdown checklist copied from somewhere else. Lines that
+/// are checklist items are read the same as in the task file, and other lines that aren't empty
+/// become tasks with the line as their description, with any list marker removed
+///
+/// # Arguments
+///
+/// * `text` - The text to read
+pub fn tasks_from_markdown(text: &str) -> Vec<Task> {
+    let mut indents: Vec<usize> = vec![];
+
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| match is_task_line(line) {
+            true => parse_task_line(line, &mut indents),
+            false => {
+                indents.clear();
+                let trimmed = line.trim();
+                let description = ["- ", "* ", "+ "]
+                    .iter()
+                    .find_map(|marker| trimmed.strip_prefix(marker))
+                    .unwrap_or(trimmed);
+                Task::new(description.trim().to_string())
+            }
+        })
+        .collect()
+}
 
-    Ok(lists)
+/// Returns the width of the whitespace at the start of a line, with tabs counting as 4 spaces
+///
+/// # Arguments
+///
+/// * `line` - The line to measure
+fn indent_width(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
 }
 
 /// Generates a fresh start for the program
@@ -87,13 +492,176 @@ pub fn deserialise() -> Result<Vec<List>> {
 ///
 /// # Returns
 ///
-/// A vector of `List` structs with one element, a `List` with the given name
-pub fn new_tasks_data<T: ToString>(list_name: T) -> Vec<List> {
-    let mut name = list_name.to_string();
+/// A document with one list, which has the given name
+pub fn new_tasks_data<T: ToString>(list_name: T) -> Document {
+    let mut document = Document {
+        preamble: vec![],
+        lists: vec![],
+    };
+    document.add_list(list_name);
+    document
+}
+
+impl Document {
+    /// Adds an empty list after the other lists, such as to a task file that has none so that it
+    /// can be opened
+    ///
+    /// # Arguments
+    ///
+    /// * `list_name` - The name the list should have, or `Main` if it is empty
+    pub fn add_list<T: ToString>(&mut self, list_name: T) {
+        let mut name = list_name.to_string();
+
+        if name.is_empty() {
+            name = "Main".to_string();
+        }
 
-    if name.is_empty() {
-        name = "Main".to_string();
+        self.lists
+            .push(List::new(name).expect("Name can never be empty"));
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes the text to a task file of its own, reads it, and removes the file
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the file, which should be different for each test
+    /// * `text` - What the file contains
+    fn read(name: &str, text: &str) -> Document {
+        let path = std::env::temp_dir().join(format!("tasks-{}-{}.md", std::process::id(), name));
+        fs::write(&path, text).unwrap();
+        let document = deserialise(&path);
+        fs::remove_file(&path).unwrap();
+        document.unwrap()
+    }
+
+    #[test]
+    fn unknown_checkboxes_are_kept_as_notes() {
+        let document = read(
+            "unknown",
+            "# Todo\n- [ ] one\n- [-] cancelled\n  - [/] started\n",
+        );
+        let tasks = &document.lists[0];
 
-    vec![List::new(name).expect("Name can never be empty")]
+        assert_eq!(tasks.length(), 1);
+        assert_eq!(
+            tasks.tasks_iter().next().unwrap().notes(),
+            ["- [-] cancelled", "  - [/] started"]
+        );
+    }
+
+    #[test]
+    fn tasks_before_the_first_list_are_kept_in_the_preamble() {
+        let document = read("preamble", "- [ ] loose\n- [x] done\n\n# Todo\n- [ ] one\n");
+
+        assert_eq!(document.preamble, ["- [ ] loose", "- [x] done"]);
+        assert_eq!(document.lists.len(), 1);
+        assert_eq!(document.lists[0].length(), 1);
+    }
+
+    #[test]
+    fn failed_saves_remove_the_temporary_file() {
+        // A directory can't be replaced by the task file, so the save fails after writing it
+        let path = std::env::temp_dir().join(format!("tasks-{}-failed.md", std::process::id()));
+        fs::create_dir_all(&path).unwrap();
+        let result = serialise(&path, &new_tasks_data("Todo"), 0);
+        let temp_left = temp_path(&path).exists();
+        fs::remove_dir(&path).unwrap();
+
+        assert!(result.is_err());
+        assert!(!temp_left);
+    }
+
+    #[test]
+    fn files_without_lists_can_have_one_added() {
+        for (name, text) in [("empty", ""), ("blank", "\n  \n")] {
+            let mut document = read(name, text);
+            assert!(document.lists.is_empty());
+
+            document.add_list("Inbox");
+            assert_eq!(document.lists.len(), 1);
+            assert_eq!(document.lists[0].name(), "Inbox");
+        }
+    }
+
+    #[test]
+    fn files_that_arent_text_say_which_line_isnt() {
+        let path = std::env::temp_dir().join(format!("tasks-{}-binary.md", std::process::id()));
+        fs::write(&path, b"# Todo\n- [ ] one\n- [ ] \xff\n").unwrap();
+        let result = deserialise(&path);
+        fs::remove_file(&path).unwrap();
+
+        match result {
+            Err(TasksError::Parse { line, .. }) => assert_eq!(line, 3),
+            _ => panic!("a file that isn't UTF-8 was read"),
+        }
+    }
+
+    /// Writes the document to a task file of its own, and returns what was written
+    ///
+    /// # Arguments
+    ///
+    /// * `name`     - The name of the file, which should be different for each test
+    /// * `document` - The document to write
+    fn write(name: &str, document: &Document) -> String {
+        let path = std::env::temp_dir().join(format!("tasks-{}-{}.md", std::process::id(), name));
+        serialise(&path, document, 0).unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        written
+    }
+
+    #[test]
+    fn task_files_are_written_back_byte_for_byte() {
+        let text = include_str!("testdata/round_trip.md");
+        let document = read("round-trip", text);
+
+        assert_eq!(write("round-trip-written", &document), text);
+    }
+
+    #[test]
+    fn blank_lines_before_a_heading_stay_before_it() {
+        let mut document = read("blank", "# Todo\n- [ ] one\n\n\n## Doing\n- [ ] two\n");
+        document.lists[0].add_task("new one").unwrap();
+        document.add_list("Done");
+
+        assert_eq!(
+            write("blank-written", &document),
+            "# Todo\n- [ ] one\n- [ ] new one\n\n\n## Doing\n- [ ] two\n\n# Done\n"
+        );
+    }
+
+    #[test]
+    fn checkboxes_are_read_as_tasks() {
+        let document = read("checkboxes", "# Todo\n- [ ] one\n- [x] two\n- [X] three\n");
+        let statuses: Vec<bool> = document.lists[0].tasks_iter().map(Task::status).collect();
+
+        assert_eq!(statuses, [false, true, true]);
+    }
+
+    #[test]
+    fn indented_tasks_are_read_as_subtasks() {
+        let text = "# Todo\n- [ ] a\n    - [ ] b\n\t\t- [ ] c\n  - [ ] d\n- [ ] e\n";
+        let document = read("subtasks", text);
+        let depths: Vec<usize> = document.lists[0].tasks_iter().map(Task::depth).collect();
+
+        assert_eq!(depths, [0, 1, 2, 1, 0]);
+    }
+
+    #[test]
+    fn subtasks_are_written_with_two_spaces_for_each_level() {
+        let document = read(
+            "subtask-indents",
+            "# Todo\n- [ ] a\n    - [x] b\n\t- [ ] c\n",
+        );
+
+        assert_eq!(
+            write("subtask-indents", &document),
+            "# Todo\n- [ ] a\n  - [x] b\n  - [ ] c\n"
+        );
+    }
 }
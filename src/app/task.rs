@@ -1,10 +1,86 @@
-use colored::Colorize;
+use crate::app::dates::{extract_due_date, today, DueFormat};
+use crate::app::error::TasksError;
+use crate::app::priority::{extract_priority, Priority, PriorityFormat};
+use crate::app::theme::theme;
+
+use chrono::NaiveDate;
+use colored::{Color, Colorize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Range;
+use std::sync::atomic::{self, AtomicUsize};
+
+/// The order the next task created will have, used to sort tasks by when they were added
+static NEXT_ORDER: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// The ways a list can be sorted
+pub enum SortKey {
+    /// Completed tasks first, followed by the rest of the tasks
+    Status,
+    /// The most important tasks first, with tasks without a priority last
+    Priority,
+    /// The tasks due soonest first, with tasks without a due date last
+    DueDate,
+    /// Alphabetically by description, ignoring case
+    Alphabetical,
+    /// The order the tasks were read from the file or added in
+    Insertion,
+}
+
+impl SortKey {
+    /// Returns what the tasks are sorted by, as it is shown to the user
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortKey::Status => "status",
+            SortKey::Priority => "priority",
+            SortKey::DueDate => "due date",
+            SortKey::Alphabetical => "description",
+            SortKey::Insertion => "the order they were added in",
+        }
+    }
+
+    /// Compares two tasks by this key
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The first task
+    /// * `b` - The second task
+    fn compare(&self, a: &Task, b: &Task) -> Ordering {
+        // None sorts before Some, so the options are compared in reverse to put None last
+        match self {
+            SortKey::Status => b.status().cmp(&a.status()),
+            SortKey::Priority => match (a.priority, b.priority) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (a, b) => b.is_some().cmp(&a.is_some()),
+            },
+            SortKey::DueDate => match (a.due, b.due) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (a, b) => b.is_some().cmp(&a.is_some()),
+            },
+            SortKey::Alphabetical => a
+                .description
+                .to_lowercase()
+                .cmp(&b.description.to_lowercase()),
+            SortKey::Insertion => a.order.cmp(&b.order),
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 /// A struct to group related tasks under a name
 pub struct List {
     tasks: Vec<Task>,
     name: String,
+    /// The level of the markdown heading the list is written as, or None if its name is written
+    /// on a line of its own like in older task files
+    heading: Option<usize>,
+    /// The lines of the task file between the name of the list and its first task that aren't
+    /// tasks, written back as they were read
+    notes: Vec<String>,
+    /// The blank lines before the name of the list in the task file, written back as they were
+    /// read, or None if the list wasn't read from the file
+    blank_lines_before: Option<Vec<String>>,
 }
 
 impl List {
@@ -15,17 +91,20 @@ impl List {
     /// * `name` - The name the list should have
     ///
     /// # Returns
-    /// An error if `name` is empty, Otherwise an Ok() containing a new List
-    pub fn new<T: ToString>(name: T) -> Result<Self, ()> {
+    /// `TasksError::EmptyName` if `name` is empty, Otherwise an Ok() containing a new List
+    pub fn new<T: ToString>(name: T) -> Result<Self, TasksError> {
         let name = name.to_string();
 
         if name.is_empty() {
-            return Err(());
+            return Err(TasksError::EmptyName);
         }
 
         Ok(Self {
             tasks: vec![],
             name,
+            heading: Some(1),
+            notes: vec![],
+            blank_lines_before: None,
         })
     }
 
@@ -34,22 +113,83 @@ impl List {
         self.name.clone()
     }
 
+    /// Returns the level of the markdown heading the list is written as, or None if its name is
+    /// written on a line of its own
+    pub fn heading(&self) -> Option<usize> {
+        self.heading
+    }
+
+    /// Sets how the name of the list is written in the task file
+    ///
+    /// # Arguments
+    /// * `heading` - The level of the markdown heading to write it as, or None to write it on a
+    ///   line of its own
+    pub fn set_heading(&mut self, heading: Option<usize>) {
+        self.heading = heading;
+    }
+
+    /// Returns the blank lines before the name of the list in the task file, or None if the list
+    /// wasn't read from the file
+    pub fn blank_lines_before(&self) -> Option<&[String]> {
+        self.blank_lines_before.as_deref()
+    }
+
+    /// Sets the blank lines read before the name of the list, so that they're written back the
+    /// same
+    ///
+    /// # Arguments
+    /// * `lines` - The blank lines, which may be empty
+    pub fn set_blank_lines_before(&mut self, lines: Vec<String>) {
+        self.blank_lines_before = Some(lines);
+    }
+
+    /// Returns the lines between the name of the list and its first task that aren't tasks
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    /// Adds lines of the task file that aren't tasks after the last task in the list, or after
+    /// its name if it has no tasks, so that they're written back in the same place
+    ///
+    /// # Arguments
+    /// * `notes` - The lines to add
+    pub fn add_notes(&mut self, notes: Vec<String>) {
+        match self.tasks.last_mut() {
+            Some(task) => task.notes.extend(notes),
+            None => self.notes.extend(notes),
+        }
+    }
+
     /// Returns an iterator of the tasks contained in the list
-    pub fn tasks_iter(&self) -> std::slice::Iter<Task> {
+    pub fn tasks_iter(&self) -> std::slice::Iter<'_, Task> {
         self.tasks.iter()
     }
 
-    /// Toggles the status of the task at the given index. No error is generated if the task index
-    /// is out of bounds, the method just returns early.
+    /// Toggles the status of the task at the given index
     ///
     /// # Arguments
     /// * `index` - The index of the task to toggle
-    pub fn toggle_task(&mut self, index: usize) {
-        if index >= self.length() {
-            return;
-        }
-
+    ///
+    /// # Returns
+    /// `TasksError::IndexOutOfRange` if there is no task at the index
+    pub fn toggle_task(&mut self, index: usize) -> Result<(), TasksError> {
+        self.check_index(index)?;
         self.tasks[index].toggle_status();
+        Ok(())
+    }
+
+    /// Makes sure there is a task at the given index
+    ///
+    /// # Arguments
+    /// * `index` - The index to check
+    fn check_index(&self, index: usize) -> Result<(), TasksError> {
+        match index < self.length() {
+            true => Ok(()),
+            false => Err(TasksError::IndexOutOfRange {
+                index,
+                len: self.length(),
+            }),
+        }
     }
 
     /// Returns the number of tasks in the list
@@ -57,84 +197,461 @@ impl List {
         self.tasks.len()
     }
 
-    /// Renames the name of the list. Method returns early if `new_name` is empty
+    /// Returns how many tasks in the list are completed
+    pub fn completed_count(&self) -> usize {
+        self.tasks.iter().filter(|task| task.status()).count()
+    }
+
+    /// Renames the name of the list
     ///
     /// # Arguments
     /// * `new_name` - The new name of the list
-    pub fn rename_list<T: ToString>(&mut self, new_name: T) {
+    ///
+    /// # Returns
+    /// `TasksError::EmptyName` if `new_name` is empty, in which case the name isn't changed
+    pub fn rename_list<T: ToString>(&mut self, new_name: T) -> Result<(), TasksError> {
         let new_name = new_name.to_string();
 
         if new_name.is_empty() {
-            return;
+            return Err(TasksError::EmptyName);
         }
 
         self.name = new_name;
+        Ok(())
     }
 
-    /// Creates and adds a new task to the list. The task will not be completed by default, and the
-    /// method returns early if the description is empty
+    /// Creates and adds a new task to the list. The task will not be completed by default
     ///
     /// # Arguments
     /// * `description` - The description of the task to add
-    pub fn add_task<T: ToString>(&mut self, description: T) {
-        if description.to_string().is_empty() {
-            return;
+    ///
+    /// # Returns
+    /// `TasksError::EmptyDescription` if the description is empty
+    pub fn add_task<T: ToString>(&mut self, description: T) -> Result<(), TasksError> {
+        if description.to_string().trim().is_empty() {
+            return Err(TasksError::EmptyDescription);
         }
 
         self.tasks.push(Task::new(description));
+        Ok(())
     }
 
-    /// Inserts the given `Task` at the given index. If the index given is 1 greater than the
-    /// current length of the task vec, then it is inserted at 0. Otherwise the method returns
-    /// early to avoid a panic.
+    /// Inserts the given `Task` at the given index, which can be the length of the list to add
+    /// it to the end
     ///
     /// # Arguments
     /// * `index` - The index to insert the task at
     /// * `task`  - The task to insert
-    pub fn insert_task(&mut self, index: usize, task: Task) {
+    ///
+    /// # Returns
+    /// `TasksError::IndexOutOfRange` if the index is past the end of the list
+    pub fn insert_task(&mut self, index: usize, task: Task) -> Result<(), TasksError> {
         if index > self.tasks.len() {
-            if index - self.tasks.len() == 1 {
-                self.tasks.insert(index - 1, task);
-            }
-            return;
+            return Err(TasksError::IndexOutOfRange {
+                index,
+                len: self.length(),
+            });
         }
+
         self.tasks.insert(index, task);
+        Ok(())
     }
 
-    /// Changes the description of the task at the given index
+    /// Changes the description of the task at the given index. Like with `Task::new`, a due date
+    /// in the description is taken out of it and set as the task's due date
     ///
     /// # Arguments
     /// * `index`        - The index of the task to change the description of
     /// * `description`  - The new description of the task
-    pub fn reword_task<T: ToString>(&mut self, index: usize, description: T) {
-        if index >= self.length() || description.to_string().is_empty() {
-            return;
+    ///
+    /// # Returns
+    /// `TasksError::IndexOutOfRange` if there is no task at the index, or
+    /// `TasksError::EmptyDescription` if the description is empty
+    pub fn reword_task<T: ToString>(
+        &mut self,
+        index: usize,
+        description: T,
+    ) -> Result<(), TasksError> {
+        self.check_index(index)?;
+        if description.to_string().trim().is_empty() {
+            return Err(TasksError::EmptyDescription);
         }
 
-        self.tasks[index].description = description.to_string();
+        let task = Task::new(description);
+        self.tasks[index].description = task.description;
+        self.tasks[index].due = task.due;
+        self.tasks[index].due_format = task.due_format;
+        self.tasks[index].priority = task.priority;
+        self.tasks[index].priority_format = task.priority_format;
+        Ok(())
+    }
+
+    /// Sets or clears the priority of the task at the given index
+    ///
+    /// # Arguments
+    /// * `index`    - The index of the task
+    /// * `priority` - The new priority, or None to clear it
+    ///
+    /// # Returns
+    /// `TasksError::IndexOutOfRange` if there is no task at the index
+    pub fn set_task_priority(
+        &mut self,
+        index: usize,
+        priority: Option<Priority>,
+    ) -> Result<(), TasksError> {
+        self.check_index(index)?;
+        self.tasks[index].priority = priority;
+        Ok(())
     }
 
-    /// Deletes the task at the given index from the list. If the index is out of bounds then the
-    /// method returns early to avoid a panic.
+    /// Sets or clears the due date of the task at the given index
+    ///
+    /// # Arguments
+    /// * `index` - The index of the task
+    /// * `due`   - The new due date, or None to clear it
+    ///
+    /// # Returns
+    /// `TasksError::IndexOutOfRange` if there is no task at the index
+    pub fn set_task_due(&mut self, index: usize, due: Option<NaiveDate>) -> Result<(), TasksError> {
+        self.check_index(index)?;
+        self.tasks[index].due = due;
+        Ok(())
+    }
+
+    /// Deletes the task at the given index from the list, along with its subtasks
     ///
     /// # Arguments
     /// * `index` - The index of the task to delete
-    pub fn delete_task(&mut self, index: usize) {
+    ///
+    /// # Returns
+    /// `TasksError::IndexOutOfRange` if there is no task at the index
+    pub fn delete_task(&mut self, index: usize) -> Result<(), TasksError> {
+        self.check_index(index)?;
+        self.take_subtree(index);
+        Ok(())
+    }
+
+    /// Removes the task at the given index and its subtasks from the list
+    ///
+    /// # Arguments
+    /// * `index` - The index of the task to remove
+    ///
+    /// # Returns
+    /// The removed tasks in order, or an empty vec if the index is out of bounds
+    pub fn take_subtree(&mut self, index: usize) -> Vec<Task> {
         if index >= self.length() {
-            return;
+            return vec![];
         }
 
-        self.tasks.remove(index);
+        let end = self.subtree_end(index);
+        self.tasks.drain(index..end).collect()
     }
 
-    /// Removes completed tasks from the list
-    pub fn delete_completed_tasks(&mut self) {
-        self.tasks = self
-            .tasks
+    /// Returns copies of the task at the given index and its subtasks
+    ///
+    /// # Arguments
+    /// * `index` - The index of the task to copy
+    pub fn subtree(&self, index: usize) -> Vec<Task> {
+        if index >= self.length() {
+            return vec![];
+        }
+
+        self.tasks[index..self.subtree_end(index)].to_vec()
+    }
+
+    /// Returns copies of the tasks at the given indexes, in the order of the indexes. Indexes that
+    /// are out of bounds are skipped
+    ///
+    /// # Arguments
+    /// * `indexes` - The indexes of the tasks to copy
+    pub fn tasks_at(&self, indexes: &[usize]) -> Vec<Task> {
+        indexes
+            .iter()
+            .filter_map(|index| self.tasks.get(*index))
+            .cloned()
+            .collect()
+    }
+
+    /// Removes the tasks at the given indexes from the list
+    ///
+    /// # Arguments
+    /// * `indexes` - The indexes of the tasks to remove
+    ///
+    /// # Returns
+    /// The removed tasks in the order they were in the list
+    pub fn take_tasks(&mut self, indexes: &[usize]) -> Vec<Task> {
+        let mut taken = vec![];
+        let mut index = 0;
+        self.tasks.retain(|task| {
+            index += 1;
+            match indexes.contains(&(index - 1)) {
+                true => {
+                    taken.push(task.clone());
+                    false
+                }
+                false => true,
+            }
+        });
+        taken
+    }
+
+    /// Inserts a group of tasks at the given index, such as one taken with `take_subtree`. The
+    /// depths of the tasks are shifted so that the first task has the given depth, keeping the
+    /// shape of the group. If the index is out of bounds then the tasks are added to the end
+    ///
+    /// # Arguments
+    /// * `index` - The index to insert the tasks at
+    /// * `tasks` - The tasks to insert
+    /// * `depth` - The depth the first task should have
+    pub fn insert_subtree(&mut self, index: usize, tasks: Vec<Task>, depth: usize) {
+        let root_depth = match tasks.first() {
+            Some(task) => task.depth,
+            None => return,
+        };
+        let index = index.min(self.length());
+
+        let tasks = tasks.into_iter().map(|mut task| {
+            task.depth = (task.depth + depth).saturating_sub(root_depth);
+            task
+        });
+        self.tasks.splice(index..index, tasks);
+    }
+
+    /// Swaps the task at the given index with the task below it that has the same parent. Their
+    /// subtasks move with them. The method returns early if there is no such task
+    ///
+    /// # Arguments
+    /// * `index` - The index of the task to move
+    ///
+    /// # Returns
+    /// The new index of the task if it was moved
+    pub fn move_task_down(&mut self, index: usize) -> Option<usize> {
+        let depth = self.tasks.get(index)?.depth;
+        let next = self.subtree_end(index);
+
+        if self.tasks.get(next)?.depth != depth {
+            return None;
+        }
+
+        let next_end = self.subtree_end(next);
+        self.tasks[index..next_end].rotate_left(next - index);
+        Some(index + next_end - next)
+    }
+
+    /// Swaps the task at the given index with the task above it that has the same parent. Their
+    /// subtasks move with them. The method returns early if there is no such task
+    ///
+    /// # Arguments
+    /// * `index` - The index of the task to move
+    ///
+    /// # Returns
+    /// The new index of the task if it was moved
+    pub fn move_task_up(&mut self, index: usize) -> Option<usize> {
+        let depth = self.tasks.get(index)?.depth;
+        let prev = self.tasks[..index]
             .iter()
+            .rposition(|task| task.depth <= depth)?;
+
+        if self.tasks[prev].depth != depth {
+            return None;
+        }
+
+        self.move_task_down(prev).map(|_| prev)
+    }
+
+    /// Returns the index after the last subtask of the task at the given index
+    ///
+    /// # Arguments
+    /// * `index` - The index of the task
+    pub fn subtree_end(&self, index: usize) -> usize {
+        let depth = match self.tasks.get(index) {
+            Some(task) => task.depth,
+            None => return self.length(),
+        };
+
+        self.tasks[index + 1..]
+            .iter()
+            .position(|task| task.depth <= depth)
+            .map(|offset| index + 1 + offset)
+            .unwrap_or(self.length())
+    }
+
+    /// Returns the number of subtasks the task at the given index has, including nested ones
+    ///
+    /// # Arguments
+    /// * `index` - The index of the task
+    pub fn subtask_count(&self, index: usize) -> usize {
+        self.subtree_end(index).saturating_sub(index + 1)
+    }
+
+    /// Returns the number of subtasks of the task at the given index that aren't completed
+    ///
+    /// # Arguments
+    /// * `index` - The index of the task
+    pub fn incomplete_subtask_count(&self, index: usize) -> usize {
+        self.subtree(index)
+            .iter()
+            .skip(1)
             .filter(|task| !task.status())
-            .map(|task| task.to_owned())
-            .collect();
+            .count()
+    }
+
+    /// Sets the status of every subtask of the task at the given index
+    ///
+    /// # Arguments
+    /// * `index`  - The index of the parent task
+    /// * `status` - Whether the subtasks should be completed
+    pub fn set_subtasks_status(&mut self, index: usize, status: bool) {
+        if index >= self.length() {
+            return;
+        }
+
+        let end = self.subtree_end(index);
+        for task in &mut self.tasks[index + 1..end] {
+            task.completed = status;
+        }
+    }
+
+    /// Makes the task at the given index, and its subtasks, a subtask of the task above it. The
+    /// method returns early if there is no task above it to nest under
+    ///
+    /// # Arguments
+    /// * `index` - The index of the task to indent
+    pub fn indent_task(&mut self, index: usize) {
+        if index == 0 || index >= self.length() {
+            return;
+        }
+
+        if self.tasks[index].depth > self.tasks[index - 1].depth {
+            return;
+        }
+
+        let end = self.subtree_end(index);
+        for task in &mut self.tasks[index..end] {
+            task.depth += 1;
+        }
+    }
+
+    /// Moves the task at the given index, and its subtasks, out a level. The method returns early
+    /// if the task is not a subtask
+    ///
+    /// # Arguments
+    /// * `index` - The index of the task to outdent
+    pub fn outdent_task(&mut self, index: usize) {
+        if index >= self.length() || self.tasks[index].depth == 0 {
+            return;
+        }
+
+        let end = self.subtree_end(index);
+        for task in &mut self.tasks[index..end] {
+            task.depth -= 1;
+        }
+    }
+
+    /// Folds or unfolds the subtasks of the task at the given index
+    ///
+    /// # Arguments
+    /// * `index` - The index of the task
+    pub fn toggle_fold(&mut self, index: usize) {
+        if self.subtask_count(index) == 0 {
+            return;
+        }
+
+        self.tasks[index].folded = !self.tasks[index].folded;
+    }
+
+    /// Folds or unfolds every task in the list that has subtasks
+    ///
+    /// # Arguments
+    /// * `folded` - Whether the tasks should be folded
+    pub fn set_all_folded(&mut self, folded: bool) {
+        for index in 0..self.length() {
+            self.tasks[index].folded = folded && self.subtask_count(index) > 0;
+        }
+    }
+
+    /// Returns the indexes of the tasks that are not hidden inside a folded task
+    pub fn unfolded_indexes(&self) -> Vec<usize> {
+        let mut indexes = vec![];
+        let mut index = 0;
+
+        while index < self.length() {
+            indexes.push(index);
+            index = match self.tasks[index].folded {
+                true => self.subtree_end(index),
+                false => index + 1,
+            };
+        }
+
+        indexes
+    }
+
+    /// Unfolds every task that the task at the given index is nested in, so that it is shown
+    ///
+    /// # Arguments
+    /// * `index` - The index of the task to show
+    pub fn unfold_parents(&mut self, index: usize) {
+        let mut depth = match self.tasks.get(index) {
+            Some(task) => task.depth,
+            None => return,
+        };
+
+        for task in self.tasks[..index].iter_mut().rev() {
+            if depth == 0 {
+                break;
+            }
+            if task.depth < depth {
+                task.folded = false;
+                depth = task.depth;
+            }
+        }
+    }
+
+    /// Returns the indexes of the tasks that are not hidden inside a folded task, and that match
+    /// the given filter. Tasks with a subtask that matches are kept, so that the subtask is shown
+    /// in context
+    ///
+    /// # Arguments
+    /// * `matches` - Returns whether a task should be shown
+    pub fn filtered_indexes<F: Fn(&Task) -> bool>(&self, matches: F) -> Vec<usize> {
+        let mut keep: Vec<bool> = self.tasks.iter().map(&matches).collect();
+
+        // Walking backwards means every subtask is checked before its parent
+        for index in (0..self.length()).rev() {
+            let end = self.subtree_end(index);
+            if keep[index + 1..end].iter().any(|keep| *keep) {
+                keep[index] = true;
+            }
+        }
+
+        self.unfolded_indexes()
+            .into_iter()
+            .filter(|index| keep[*index])
+            .collect()
+    }
+
+    /// Removes completed tasks from the list, along with their subtasks
+    pub fn delete_completed_tasks(&mut self) {
+        let mut index = 0;
+
+        while index < self.length() {
+            if self.tasks[index].status() {
+                self.take_subtree(index);
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    /// Returns a copy of the list with the given tasks in place of its own
+    ///
+    /// # Arguments
+    /// * `tasks` - The tasks the copy should have
+    pub fn with_tasks(&self, tasks: Vec<Task>) -> List {
+        List {
+            tasks,
+            ..self.clone()
+        }
     }
 
     /// Removes all tasks from the list
@@ -142,34 +659,113 @@ impl List {
         self.tasks = vec![];
     }
 
-    /// Sorts the list with completed tasks being first, followed by the rest of the tasks
-    pub fn sort_list(&mut self) {
-        self.tasks = self
-            .tasks
-            .iter()
-            .filter(|task| task.status())
-            .chain(self.tasks.iter().filter(|task| !task.status()))
-            .map(|task| task.to_owned())
-            .collect();
+    /// Sorts the list by the given key. The sort is stable, so tasks that are equal keep their
+    /// order. Subtasks are sorted amongst themselves and stay under their parent
+    ///
+    /// # Arguments
+    /// * `key` - What to sort the tasks by
+    pub fn sort_list(&mut self, key: SortKey) {
+        let tasks = std::mem::take(&mut self.tasks);
+        self.tasks = sort_subtrees(tasks, &|a: &Task, b: &Task| key.compare(a, b));
     }
 }
 
+// The blank lines before a list only say how the task file was laid out, so they aren't compared
+impl PartialEq for List {
+    fn eq(&self, other: &Self) -> bool {
+        self.tasks == other.tasks
+            && self.name == other.name
+            && self.heading == other.heading
+            && self.notes == other.notes
+    }
+}
+
+impl Eq for List {}
+
+/// Stably sorts a group of sibling tasks, keeping each task's subtasks directly beneath it and
+/// sorting them in the same way
+///
+/// # Arguments
+///
+/// * `tasks`   - The tasks to sort, the first of which must be at the shallowest depth
+/// * `compare` - The function to order tasks by
+fn sort_subtrees<F: Fn(&Task, &Task) -> Ordering>(tasks: Vec<Task>, compare: &F) -> Vec<Task> {
+    let root_depth = match tasks.first() {
+        Some(task) => task.depth,
+        None => return tasks,
+    };
+
+    let mut groups: Vec<Vec<Task>> = vec![];
+    for task in tasks {
+        match groups.last_mut() {
+            Some(group) if task.depth > root_depth => group.push(task),
+            _ => groups.push(vec![task]),
+        }
+    }
+
+    groups.sort_by(|a, b| compare(&a[0], &b[0]));
+
+    groups
+        .into_iter()
+        .flat_map(|mut group| {
+            let subtasks = sort_subtrees(group.split_off(1), compare);
+            group.into_iter().chain(subtasks)
+        })
+        .collect()
+}
+
 #[derive(Clone, Debug)]
 /// Struct to represent a task
 pub struct Task {
     description: String,
     completed: bool,
+    /// How many tasks this task is nested under
+    depth: usize,
+    /// Whether the task's subtasks are hidden
+    folded: bool,
+    due: Option<NaiveDate>,
+    /// How the due date is written in the task file, so it's written back the same way
+    due_format: DueFormat,
+    priority: Option<Priority>,
+    /// How the priority is written in the task file, so it's written back the same way
+    priority_format: PriorityFormat,
+    /// When the task was created relative to other tasks, used to sort by insertion order
+    order: usize,
+    /// The lines of the task file after the task that aren't tasks, written back as they were
+    /// read
+    notes: Vec<String>,
 }
 
 impl Task {
-    /// Creates a new task
+    /// Creates a new task. If the description contains a due date, written as `due:2026-10-20`
+    /// or `📅 2026-10-20`, then it is taken out of the description and set as the due date.
+    /// Priorities written as `(A)` or `!!!` are taken out in the same way
     ///
     /// # Arguments
     /// * `description` - The description the task should have
     pub fn new<T: ToString>(description: T) -> Task {
+        let (description, due) = extract_due_date(&description.to_string());
+        let (due, due_format) = match due {
+            Some((date, format)) => (Some(date), format),
+            None => (None, DueFormat::default()),
+        };
+        let (description, priority) = extract_priority(&description);
+        let (priority, priority_format) = match priority {
+            Some((priority, format)) => (Some(priority), format),
+            None => (None, PriorityFormat::default()),
+        };
+
         Self {
-            description: description.to_string(),
+            description,
             completed: false,
+            depth: 0,
+            folded: false,
+            due,
+            due_format,
+            priority,
+            priority_format,
+            order: NEXT_ORDER.fetch_add(1, atomic::Ordering::Relaxed),
+            notes: vec![],
         }
     }
 
@@ -183,23 +779,418 @@ impl Task {
         self.completed
     }
 
-    /// Returns the tasks description
-    pub fn description(&self) -> String {
-        self.description.clone()
+    /// Returns how many tasks this task is nested under
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Sets how many tasks this task is nested under
+    ///
+    /// # Arguments
+    /// * `depth` - The new depth of the task
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+    }
+
+    /// Returns the lines after the task that aren't tasks
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    /// Returns whether the task's subtasks are hidden
+    pub fn is_folded(&self) -> bool {
+        self.folded
+    }
+
+    /// Returns the date the task is due, if it has one
+    pub fn due(&self) -> Option<NaiveDate> {
+        self.due
+    }
+
+    /// Returns how important the task is, if it has a priority
+    pub fn priority(&self) -> Option<Priority> {
+        self.priority
     }
-}
 
-impl ToString for Task {
-    fn to_string(&self) -> String {
-        let mut string = String::new();
+    /// Returns the tags in the task's description, which are words starting with `#` or `+`
+    pub fn tags(&self) -> Vec<String> {
+        self.description
+            .split_whitespace()
+            .filter(|word| is_tag(word))
+            .map(|word| word.to_string())
+            .collect()
+    }
+
+    /// Returns whether the task has the given tag, ignoring case. If the tag doesn't start with
+    /// `#` or `+` then either is matched
+    ///
+    /// # Arguments
+    /// * `tag` - The tag to look for
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = tag.to_lowercase();
+        self.tags().iter().any(|task_tag| {
+            let task_tag = task_tag.to_lowercase();
+            task_tag == tag || task_tag[1..] == tag
+        })
+    }
+
+    /// Finds the first place the given text appears in the task's description. The search ignores
+    /// case unless the text contains an uppercase letter
+    ///
+    /// # Arguments
+    /// * `pattern` - The text to search for
+    ///
+    /// # Returns
+    /// The byte range of the match in the description, if there is one
+    pub fn find(&self, pattern: &str) -> Option<Range<usize>> {
+        self.description
+            .char_indices()
+            .find_map(|(start, _)| self.match_at(start, pattern))
+    }
+
+    /// Finds every place the given text appears in the task's description, without overlapping.
+    /// The search ignores case in the same way as [`Task::find`]
+    ///
+    /// # Arguments
+    /// * `pattern` - The text to search for
+    ///
+    /// # Returns
+    /// The byte ranges of the matches in the description, in order
+    pub fn find_all(&self, pattern: &str) -> Vec<Range<usize>> {
+        let mut matches: Vec<Range<usize>> = vec![];
+        for (start, _) in self.description.char_indices() {
+            if matches.last().is_some_and(|last| start < last.end) {
+                continue;
+            }
+            if let Some(found) = self.match_at(start, pattern) {
+                matches.push(found);
+            }
+        }
+        matches
+    }
 
-        string.push_str(&match self.completed {
-            true => format!("[{}] ", "✔".bright_green()),
+    /// Returns the range of the match of the given text starting at the given byte index of the
+    /// description, if the text appears there
+    ///
+    /// # Arguments
+    /// * `start` - The byte index the match has to start at
+    /// * `pattern` - The text to search for
+    fn match_at(&self, start: usize, pattern: &str) -> Option<Range<usize>> {
+        if pattern.is_empty() {
+            return None;
+        }
+        let case_sensitive = pattern.chars().any(char::is_uppercase);
+        let mut end = start;
+        let mut chars = self.description[start..].chars();
+
+        for p in pattern.chars() {
+            let c = chars.next()?;
+            let equal = match case_sensitive {
+                true => c == p,
+                false => c.to_lowercase().eq(p.to_lowercase()),
+            };
+            if !equal {
+                return None;
+            }
+            end += c.len_utf8();
+        }
+
+        Some(start..end)
+    }
+
+    /// Returns the task as it is shown in the app, with every match of the given text highlighted
+    ///
+    /// # Arguments
+    /// * `pattern` - The text to highlight
+    pub fn highlighted(&self, pattern: &str) -> String {
+        let mut rendered = self.prefix();
+        rendered.push_str(&self.styled_description(&self.find_all(pattern)));
+        rendered.push_str(&self.styled_due());
+        rendered
+    }
+
+    /// Returns what is drawn before the description, which is the indentation, the checkbox and
+    /// the priority
+    fn prefix(&self) -> String {
+        let mut prefix = format!("{}{}", "  ".repeat(self.depth), self.checkbox());
+        if let Some(priority) = self.priority {
+            prefix.push_str(&format!("{} ", priority.coloured()));
+        }
+        prefix
+    }
+
+    /// Returns the colour the whole task is drawn in if it is overdue or due today
+    fn due_colour(&self) -> Option<Color> {
+        match self.due {
+            Some(due) if !self.completed && due < today() => Some(theme().overdue),
+            Some(due) if !self.completed && due == today() => Some(theme().due_today),
+            _ => None,
+        }
+    }
+
+    /// Returns the description as it is shown in the app. Tags are coloured unless the whole
+    /// description is coloured because of its due date, and the highlighted text is coloured over
+    /// both
+    ///
+    /// # Arguments
+    ///
+    /// * `highlights` - The ranges of the description to highlight
+    fn styled_description(&self, highlights: &[Range<usize>]) -> String {
+        let mut tags = vec![];
+        let mut start = 0;
+        for word in self.description.split(' ') {
+            if is_tag(word) {
+                tags.push(start..start + word.len());
+            }
+            start += word.len() + 1;
+        }
+
+        // Splitting the description into runs of characters that are styled the same way
+        let mut runs: Vec<(Range<usize>, bool, bool)> = vec![];
+        for (i, c) in self.description.char_indices() {
+            let highlighted = highlights.iter().any(|range| range.contains(&i));
+            let tag = tags.iter().any(|tag| tag.contains(&i));
+            match runs.last_mut() {
+                Some((range, h, t)) if (*h, *t) == (highlighted, tag) => {
+                    range.end = i + c.len_utf8()
+                }
+                _ => runs.push((i..i + c.len_utf8(), highlighted, tag)),
+            }
+        }
+
+        runs.into_iter()
+            .map(|(range, highlighted, tag)| {
+                let text = &self.description[range];
+                match (highlighted, tag, self.due_colour()) {
+                    (true, _, _) => text.black().on_bright_yellow().to_string(),
+                    (false, _, Some(colour)) => text.color(colour).to_string(),
+                    (false, true, None) => text.color(theme().tag).to_string(),
+                    (false, false, None) => text.to_string(),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the due date as it is shown after the description, or nothing if there isn't one
+    fn styled_due(&self) -> String {
+        let due = match self.due {
+            Some(due) => format!("due {}", due).dimmed(),
+            None => return String::new(),
+        };
+        match self.due_colour() {
+            Some(colour) => format!(" {}", due.color(colour)),
+            None => format!(" {}", due),
+        }
+    }
+
+    /// Returns the checkbox drawn before the task, followed by a space
+    pub fn checkbox(&self) -> String {
+        match self.completed {
+            true => format!("[{}] ", theme().checkmark.color(theme().checkmark_colour)),
             false => "[ ] ".to_string(),
-        });
+        }
+    }
+
+    /// Returns the text of the task as it is written in the task file, which is the description
+    /// with its priority, followed by the due date if there is one
+    pub fn text(&self) -> String {
+        let text = match self.priority {
+            Some(priority) => self.priority_format.apply(priority, &self.description),
+            None => self.description.clone(),
+        };
+
+        match self.due {
+            Some(due) => format!("{} {}", text, self.due_format.format(due)),
+            None => text,
+        }
+    }
+}
+
+// Whether a task is folded is only part of how it's shown, and its order only says when it was
+// created, so neither are compared
+impl PartialEq for Task {
+    fn eq(&self, other: &Self) -> bool {
+        self.description == other.description
+            && self.completed == other.completed
+            && self.depth == other.depth
+            && self.due == other.due
+            && self.priority == other.priority
+            && self.notes == other.notes
+    }
+}
+
+impl Eq for Task {}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.prefix(),
+            self.styled_description(&[]),
+            self.styled_due()
+        )
+    }
+}
+
+/// Returns whether a word is a tag, meaning it starts with `#` or `+` followed by a letter or
+/// number
+///
+/// # Arguments
+/// * `word` - The word to check
+fn is_tag(word: &str) -> bool {
+    let mut chars = word.chars();
+    matches!(chars.next(), Some('#') | Some('+'))
+        && chars.next().is_some_and(|c| c.is_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the task as it is shown in the app with the pattern highlighted, with colours on
+    fn highlight(task: &Task, pattern: &str) -> String {
+        colored::control::set_override(true);
+        task.highlighted(pattern)
+    }
+
+    #[test]
+    fn lists_can_be_sorted_back_into_the_order_tasks_were_added_in() {
+        let mut list = List::new("Todo").unwrap();
+        for description in ["c", "a", "b"] {
+            list.add_task(description).unwrap();
+        }
+        let descriptions =
+            |list: &List| -> Vec<String> { list.tasks_iter().map(Task::text).collect() };
+
+        list.sort_list(SortKey::Alphabetical);
+        assert_eq!(descriptions(&list), ["a", "b", "c"]);
+        list.sort_list(SortKey::Insertion);
+        assert_eq!(descriptions(&list), ["c", "a", "b"]);
+    }
+
+    /// Returns a list of tasks nested like this:
+    ///
+    /// ```text
+    /// 0 a
+    /// 1   a1
+    /// 2     a1a
+    /// 3 b
+    /// 4   b1
+    /// ```
+    fn nested_list() -> List {
+        let mut list = List::new("Main").unwrap();
+        for description in ["a", "a1", "a1a", "b", "b1"] {
+            list.add_task(description).unwrap();
+        }
+        for index in [1, 2, 2, 4] {
+            list.indent_task(index);
+        }
+        list
+    }
+
+    #[test]
+    fn folding_a_task_hides_all_of_its_subtasks() {
+        let mut list = nested_list();
+        assert_eq!(list.subtask_count(0), 2);
+
+        list.toggle_fold(0);
+        assert_eq!(list.unfolded_indexes(), [0, 3, 4]);
+        list.toggle_fold(3);
+        assert_eq!(list.unfolded_indexes(), [0, 3]);
+        list.toggle_fold(0);
+        assert_eq!(list.unfolded_indexes(), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn tasks_without_subtasks_cant_be_folded() {
+        let mut list = nested_list();
+        list.toggle_fold(2);
+        list.set_all_folded(true);
+        list.toggle_fold(0);
+
+        assert_eq!(list.unfolded_indexes(), [0, 1, 3]);
+    }
+
+    #[test]
+    fn showing_a_subtask_unfolds_the_tasks_it_is_in() {
+        let mut list = nested_list();
+        list.set_all_folded(true);
+        assert_eq!(list.unfolded_indexes(), [0, 3]);
+
+        list.unfold_parents(2);
+        assert_eq!(list.unfolded_indexes(), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn a_filter_keeps_the_tasks_a_matching_subtask_is_in() {
+        let list = nested_list();
+        let shown = list.filtered_indexes(|task| task.text() == "a1a");
+
+        assert_eq!(shown, [0, 1, 2]);
+    }
+
+    #[test]
+    fn subtasks_move_with_their_parent() {
+        let mut list = nested_list();
+        assert_eq!(list.move_task_down(0), Some(2));
+
+        let descriptions: Vec<String> = list.tasks_iter().map(Task::text).collect();
+        let depths: Vec<usize> = list.tasks_iter().map(Task::depth).collect();
+        assert_eq!(descriptions, ["b", "b1", "a", "a1", "a1a"]);
+        assert_eq!(depths, [0, 1, 0, 1, 2]);
+        assert_eq!(list.move_task_down(3), None);
+    }
+
+    #[test]
+    fn highlights_only_the_match_in_the_description() {
+        let mut task = Task::new("m 31 #work");
+        task.toggle_status();
+        let highlighted = highlight(&task, "3");
+
+        assert!(highlighted.starts_with(&task.prefix()));
+        assert!(highlighted.contains(&"3".black().on_bright_yellow().to_string()));
+        assert!(highlighted.contains(&"#work".color(theme().tag).to_string()));
+    }
+
+    #[test]
+    fn searching_escape_code_text_leaves_the_codes_alone() {
+        let task = Task::new("#tag m");
+        for pattern in ["m", "1", "3", "["] {
+            let highlighted = highlight(&task, pattern);
+            assert!(highlighted.contains(&"#tag".color(theme().tag).to_string()));
+        }
+    }
+
+    #[test]
+    fn the_checkbox_isnt_highlighted() {
+        let mut task = Task::new("mm");
+        task.toggle_status();
+        let highlighted = highlight(&task, "m");
+
+        assert!(highlighted.starts_with(&task.checkbox()));
+        assert!(highlighted.ends_with(&"mm".black().on_bright_yellow().to_string()));
+    }
 
-        string.push_str(&self.description);
+    #[test]
+    fn every_match_in_the_description_is_highlighted() {
+        let task = Task::new("Ana and anna");
+        assert_eq!(task.find_all("an"), [0..2, 4..6, 8..10]);
+        assert_eq!(task.find_all("aa"), []);
+        assert_eq!(Task::new("aaaa").find_all("aa"), [0..2, 2..4]);
 
-        string
+        let highlighted = highlight(&task, "an");
+        assert_eq!(
+            highlighted
+                .matches(&"An".black().on_bright_yellow().to_string())
+                .count(),
+            1
+        );
+        assert_eq!(
+            highlighted
+                .matches(&"an".black().on_bright_yellow().to_string())
+                .count(),
+            2
+        );
     }
 }
@@ -0,0 +1,146 @@
+use crate::app::error::TasksError;
+use crate::app::serialisation::{deserialise, serialise, Document};
+use crate::app::task::List;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// When a file was last modified and how long it is, which changes whenever the file is written
+type Stamp = Option<(SystemTime, u64)>;
+
+/// The task file the lists are read from and saved to. What the file contained when it was last
+/// read or saved is kept, so that changes made to it outside the app can be noticed and merged
+pub struct TaskFile {
+    path: PathBuf,
+    /// The lines before the first list in the file
+    preamble: Vec<String>,
+    /// The lists as they were when the file was last read or saved
+    base: Vec<List>,
+    stamp: Stamp,
+    /// The stamp of the version of the file that was written outside the app but couldn't be
+    /// read, if that is what the file contains now
+    unreadable: Option<Stamp>,
+    /// How many previous versions of the file to keep when it is saved
+    backups: usize,
+    /// Whether the file has been backed up since it was last read. Only the first save after
+    /// reading it makes a backup, so saving after every change doesn't push the version that
+    /// was read out of the backups
+    backed_up: bool,
+}
+
+impl TaskFile {
+    /// Creates a new TaskFile
+    ///
+    /// # Arguments
+    ///
+    /// * `path`     - The path of the task file
+    /// * `document` - What was read from the file, or what it should contain if it doesn't exist
+    /// * `backups`  - How many previous versions of the file to keep when it is saved
+    pub fn new<P: AsRef<Path>>(path: P, document: Document, backups: usize) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            preamble: document.preamble,
+            base: document.lists,
+            stamp: stamp(path.as_ref()),
+            unreadable: None,
+            backups,
+            backed_up: false,
+        }
+    }
+
+    /// Returns the path of the task file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the lists as they were when the file was last read or saved
+    pub fn base(&self) -> &[List] {
+        &self.base
+    }
+
+    /// Returns whether the file has been written to since it was last read or saved, not counting
+    /// a version of it that has already been found to be unreadable
+    pub fn has_changed(&self) -> bool {
+        let current = stamp(&self.path);
+        current != self.stamp && Some(current) != self.unreadable
+    }
+
+    /// Returns whether the file contains a version written outside the app that couldn't be read,
+    /// which shouldn't be saved over without asking the user
+    pub fn is_unreadable(&self) -> bool {
+        self.unreadable.is_some() && Some(stamp(&self.path)) == self.unreadable
+    }
+
+    /// Reads the file again. If it can't be read, or has no lists left in it, what was last read or
+    /// saved is kept as the base, and the file won't count as changed until it is written to again
+    ///
+    /// # Returns
+    ///
+    /// The lists in the file, or why it couldn't be read
+    pub fn reload(&mut self) -> Result<Vec<List>, TasksError> {
+        let current = stamp(&self.path);
+        let document =
+            deserialise(&self.path).and_then(|document| match document.lists.is_empty() {
+                true => Err(TasksError::NoLists),
+                false => Ok(document),
+            });
+        let document = match document {
+            Ok(document) => document,
+            Err(e) => {
+                self.unreadable = Some(current);
+                return Err(e);
+            }
+        };
+
+        self.preamble = document.preamble;
+        self.base = document.lists.clone();
+        self.stamp = current;
+        self.unreadable = None;
+        self.backed_up = false;
+        Ok(document.lists)
+    }
+
+    /// Returns the document that would be written to the file
+    ///
+    /// # Arguments
+    ///
+    /// * `lists` - The lists to write
+    pub fn document(&self, lists: &[List]) -> Document {
+        Document {
+            preamble: self.preamble.clone(),
+            lists: lists.to_vec(),
+        }
+    }
+
+    /// Writes the lists to the file, backing it up first if this is the first save since it was
+    /// read
+    ///
+    /// # Arguments
+    ///
+    /// * `lists` - The lists to write
+    pub fn save(&mut self, lists: &[List]) -> Result<(), TasksError> {
+        let document = self.document(lists);
+        let backups = match self.backed_up {
+            true => 0,
+            false => self.backups,
+        };
+        serialise(&self.path, &document, backups)?;
+
+        self.base = document.lists;
+        self.stamp = stamp(&self.path);
+        self.unreadable = None;
+        self.backed_up = true;
+        Ok(())
+    }
+}
+
+/// Returns when the file at the given path was last modified and how long it is, or None if it
+/// doesn't exist
+///
+/// # Arguments
+///
+/// * `path` - The path of the file
+fn stamp(path: &Path) -> Stamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
@@ -0,0 +1,50 @@
+use colored::Color;
+use std::sync::OnceLock;
+
+/// The theme the app is drawn with, which is set once from the config file when the app starts
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+#[derive(Clone, Debug)]
+/// The glyphs and colours used to draw tasks
+pub struct Theme {
+    /// What is drawn inside the checkbox of a completed task
+    pub checkmark: String,
+    pub checkmark_colour: Color,
+    /// The colour of tasks that are overdue
+    pub overdue: Color,
+    /// The colour of tasks that are due today
+    pub due_today: Color,
+    pub tag: Color,
+    pub priority_a: Color,
+    pub priority_b: Color,
+    pub priority_c: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            checkmark: "✔".to_string(),
+            checkmark_colour: Color::BrightGreen,
+            overdue: Color::Red,
+            due_today: Color::Yellow,
+            tag: Color::Cyan,
+            priority_a: Color::BrightRed,
+            priority_b: Color::BrightYellow,
+            priority_c: Color::BrightBlue,
+        }
+    }
+}
+
+/// Sets the theme the app is drawn with. Only the first theme set is used
+///
+/// # Arguments
+///
+/// * `theme` - The theme to use
+pub fn set_theme(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+/// Returns the theme the app is drawn with, which is the default theme if none has been set
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
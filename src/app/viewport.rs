@@ -0,0 +1,153 @@
+/// The window of tasks from a list that fits on the screen
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Viewport {
+    /// The index of the first task shown
+    offset: usize,
+    /// The index after the last task shown
+    end: usize,
+    /// The total number of tasks in the list
+    len: usize,
+    /// Whether the list is too long to fit, and lines are used to show how many tasks are hidden
+    scrolling: bool,
+}
+
+impl Viewport {
+    /// Moves the viewport so that the cursor is visible
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The index of the task the cursor is on
+    /// * `len`    - The number of tasks in the list
+    /// * `rows`   - The number of rows the list can be drawn in
+    pub fn follow(&mut self, cursor: usize, len: usize, rows: usize) {
+        self.len = len;
+        self.scrolling = len > rows;
+
+        if !self.scrolling {
+            self.offset = 0;
+            self.end = len;
+            return;
+        }
+
+        // Two rows are needed to show how many tasks are above and below the viewport
+        let task_rows = rows.saturating_sub(2).max(1);
+
+        if cursor < self.offset {
+            self.offset = cursor;
+        } else if cursor >= self.offset + task_rows {
+            self.offset = cursor + 1 - task_rows;
+        }
+        self.offset = self.offset.min(len - task_rows);
+        self.end = self.offset + task_rows;
+    }
+
+    /// Returns the range of task indexes that are visible
+    pub fn visible(&self) -> std::ops::Range<usize> {
+        self.offset..self.end
+    }
+
+    /// Returns the number of tasks hidden above the viewport
+    pub fn hidden_above(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the number of tasks hidden below the viewport
+    pub fn hidden_below(&self) -> usize {
+        self.len - self.end
+    }
+
+    /// Returns whether the list is too long for the screen
+    pub fn is_scrolling(&self) -> bool {
+        self.scrolling
+    }
+
+    /// Returns the row the given task is drawn on, relative to the first row of the list
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the task
+    pub fn row_of(&self, index: usize) -> usize {
+        let index = index.clamp(self.offset, self.end.max(self.offset + 1) - 1);
+        index - self.offset + usize::from(self.scrolling)
+    }
+
+    /// Returns the number of rows the viewport takes up when drawn
+    pub fn height(&self) -> usize {
+        self.end - self.offset + 2 * usize::from(self.scrolling)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a viewport that has followed the cursor
+    fn following(cursor: usize, len: usize, rows: usize) -> Viewport {
+        let mut viewport = Viewport::default();
+        viewport.follow(cursor, len, rows);
+        viewport
+    }
+
+    #[test]
+    fn a_list_that_fits_is_shown_whole() {
+        let viewport = following(4, 5, 5);
+
+        assert!(!viewport.is_scrolling());
+        assert_eq!(viewport.visible(), 0..5);
+        assert_eq!(viewport.height(), 5);
+        assert_eq!(viewport.row_of(3), 3);
+        assert_eq!((viewport.hidden_above(), viewport.hidden_below()), (0, 0));
+    }
+
+    #[test]
+    fn a_long_list_leaves_rows_for_the_hidden_counts() {
+        let viewport = following(0, 20, 7);
+
+        assert!(viewport.is_scrolling());
+        assert_eq!(viewport.visible(), 0..5);
+        assert_eq!(viewport.height(), 7);
+        assert_eq!(viewport.row_of(0), 1);
+        assert_eq!((viewport.hidden_above(), viewport.hidden_below()), (0, 15));
+    }
+
+    #[test]
+    fn the_viewport_only_scrolls_once_the_cursor_leaves_it() {
+        let mut viewport = following(0, 20, 7);
+
+        viewport.follow(9, 20, 7);
+        assert_eq!(viewport.visible(), 5..10);
+        assert_eq!(viewport.row_of(9), 5);
+
+        viewport.follow(6, 20, 7);
+        assert_eq!(viewport.visible(), 5..10);
+
+        viewport.follow(3, 20, 7);
+        assert_eq!(viewport.visible(), 3..8);
+        assert_eq!((viewport.hidden_above(), viewport.hidden_below()), (3, 12));
+    }
+
+    #[test]
+    fn resizing_the_screen_keeps_the_viewport_full() {
+        let mut viewport = following(19, 20, 7);
+        assert_eq!(viewport.visible(), 15..20);
+
+        viewport.follow(19, 20, 12);
+        assert_eq!(viewport.visible(), 10..20);
+
+        viewport.follow(19, 20, 30);
+        assert!(!viewport.is_scrolling());
+        assert_eq!(viewport.visible(), 0..20);
+
+        viewport.follow(19, 20, 7);
+        assert_eq!(viewport.visible(), 15..20);
+    }
+
+    #[test]
+    fn deleting_tasks_at_the_end_doesnt_leave_empty_rows() {
+        let mut viewport = following(19, 20, 7);
+
+        viewport.follow(7, 8, 7);
+        assert_eq!(viewport.visible(), 3..8);
+        assert_eq!(viewport.hidden_below(), 0);
+    }
+}
@@ -0,0 +1,342 @@
+use std::env;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use crate::app::DEFAULT_FILE_NAME;
+
+/// The environment variable that can be used to set the task file
+const FILE_ENV_VAR: &str = "TASKS_FILE";
+
+/// The usage message printed by `--help`
+const USAGE: &str = "Usage: tasks [OPTIONS] [FILE]
+       tasks [OPTIONS] <COMMAND>
+
+Arguments:
+  [FILE]  The task file to open
+
+Commands:
+  add <LIST> <DESCRIPTION>  Add a task to the end of a list
+  done <LIST> <NUMBER>      Mark a task as completed
+  ls [LIST]                 Print every list, or only the given list
+  rm <LIST> <NUMBER>        Delete a task, along with its subtasks
+
+Options:
+  -f, --file <FILE>  The task file to use
+      --full-screen  Draw the app on the whole terminal, in the alternate screen
+      --inline       Draw the app below the prompt, overriding full_screen in the config
+  -h, --help         Print this message
+  -V, --version      Print the version
+
+The task file defaults to the TASKS_FILE environment variable, or .tasks.md in the current
+directory if it is not set. Tasks are numbered from 1, in the order `tasks ls` prints them";
+
+/// The arguments the program was started with
+#[derive(Debug, PartialEq, Eq)]
+pub struct Args {
+    /// The path of the task file to read from and write to
+    pub file: PathBuf,
+    /// The subcommand to run instead of the interactive app, if any
+    pub subcommand: Option<Subcommand>,
+    /// Whether to draw the app on the whole terminal, if it was chosen on the command line
+    pub full_screen: Option<bool>,
+}
+
+/// A non-interactive command that edits or prints the task file
+#[derive(Debug, PartialEq, Eq)]
+pub enum Subcommand {
+    /// Adds a task with the given description to the end of a list
+    Add { list: String, description: String },
+    /// Marks the task with the given number as completed
+    Done { list: String, number: usize },
+    /// Prints the given list, or every list if none is given
+    List { list: Option<String> },
+    /// Deletes the task with the given number
+    Remove { list: String, number: usize },
+}
+
+/// What the program should do after parsing the command line
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Run the app with the given arguments
+    Run(Args),
+    /// Print the given message and exit
+    Print(String),
+}
+
+/// Parses the command line arguments the program was started with
+///
+/// The task file is chosen in the following order
+/// - The `FILE` argument or the `--file` flag
+/// - The `TASKS_FILE` environment variable
+/// - `.tasks.md` in the current directory
+///
+/// If the first argument that isn't an option is the name of a subcommand, the remaining arguments
+/// are passed to that subcommand instead of being treated as a file
+///
+/// # Returns
+///
+/// The command the program should carry out, or an error message if the arguments are invalid
+pub fn parse_args() -> Result<Command, String> {
+    parse_args_from(env::args().skip(1), env::var_os(FILE_ENV_VAR))
+}
+
+/// Parses the given command line arguments, in the same way as `parse_args`
+///
+/// # Arguments
+///
+/// * `args`     - The arguments after the name of the program
+/// * `env_file` - The value of the `TASKS_FILE` environment variable, if it is set
+fn parse_args_from<I: Iterator<Item = String>>(
+    mut args: I,
+    env_file: Option<OsString>,
+) -> Result<Command, String> {
+    let mut file: Option<PathBuf> = None;
+    let mut positionals: Vec<String> = vec![];
+    let mut full_screen: Option<bool> = None;
+
+    while let Some(arg) = args.next() {
+        let path = match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Print(USAGE.to_string())),
+            "-V" | "--version" => {
+                return Ok(Command::Print(format!(
+                    "tasks {}",
+                    env!("CARGO_PKG_VERSION")
+                )))
+            }
+            "--full-screen" => {
+                full_screen = Some(true);
+                continue;
+            }
+            "--inline" => {
+                full_screen = Some(false);
+                continue;
+            }
+            "-f" | "--file" => match args.next() {
+                Some(path) => path,
+                None => return Err(format!("{} requires a file path", arg)),
+            },
+            "--" => {
+                positionals.extend(args.by_ref());
+                break;
+            }
+            _ if arg.starts_with("--file=") => arg["--file=".len()..].to_string(),
+            _ if arg.starts_with('-') && arg.len() > 1 && positionals.is_empty() => {
+                return Err(format!("Unknown option '{}'", arg))
+            }
+            _ => {
+                positionals.push(arg);
+                continue;
+            }
+        };
+
+        if file.is_some() {
+            return Err("Only one task file can be given".to_string());
+        }
+        file = Some(PathBuf::from(path));
+    }
+
+    let mut positionals = positionals.into_iter();
+    let subcommand = match positionals.next() {
+        Some(first) => match parse_subcommand(&first, &mut positionals)? {
+            Some(subcommand) => Some(subcommand),
+            None => {
+                if file.is_some() {
+                    return Err("Only one task file can be given".to_string());
+                }
+                file = Some(PathBuf::from(first));
+                None
+            }
+        },
+        None => None,
+    };
+
+    if let Some(arg) = positionals.next() {
+        return Err(format!("Unexpected argument '{}'", arg));
+    }
+
+    let file = file
+        .or_else(|| env_file.filter(|path| !path.is_empty()).map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_FILE_NAME));
+
+    Ok(Command::Run(Args {
+        file,
+        subcommand,
+        full_screen,
+    }))
+}
+
+/// Parses a subcommand and its arguments
+///
+/// # Arguments
+///
+/// * `name` - The name of the subcommand
+/// * `args` - The arguments following the name, only the ones the subcommand uses are consumed
+///
+/// # Returns
+///
+/// None if `name` is not a subcommand, otherwise the parsed subcommand or an error message if
+/// its arguments are invalid
+fn parse_subcommand<I: Iterator<Item = String>>(
+    name: &str,
+    args: &mut I,
+) -> Result<Option<Subcommand>, String> {
+    let mut required = |what: &str| {
+        args.next()
+            .ok_or_else(|| format!("'{}' requires a {}", name, what))
+    };
+
+    let subcommand = match name {
+        "add" => Subcommand::Add {
+            list: required("list name")?,
+            description: required("task description")?,
+        },
+        "done" => Subcommand::Done {
+            list: required("list name")?,
+            number: parse_task_number(&required("task number")?)?,
+        },
+        "rm" => Subcommand::Remove {
+            list: required("list name")?,
+            number: parse_task_number(&required("task number")?)?,
+        },
+        "ls" => Subcommand::List { list: args.next() },
+        _ => return Ok(None),
+    };
+
+    Ok(Some(subcommand))
+}
+
+/// Parses a task number as printed by `tasks ls`, which starts at 1
+///
+/// # Arguments
+///
+/// * `number` - The number to parse
+fn parse_task_number(number: &str) -> Result<usize, String> {
+    match number.parse::<usize>() {
+        Ok(number) if number > 0 => Ok(number),
+        _ => Err(format!("'{}' is not a valid task number", number)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses the arguments with no `TASKS_FILE` set
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_with_env(args, None)
+    }
+
+    /// Parses the arguments with `TASKS_FILE` set to the given path, if one is given
+    fn parse_with_env(args: &[&str], env_file: Option<&str>) -> Result<Command, String> {
+        let args = args.iter().map(|arg| arg.to_string());
+        parse_args_from(args, env_file.map(OsString::from))
+    }
+
+    /// Returns the arguments the app is run with, for arguments that are expected to be valid
+    fn run(args: &[&str]) -> Args {
+        match parse(args) {
+            Ok(Command::Run(args)) => args,
+            result => panic!("{:?} didn't run the app: {:?}", args, result),
+        }
+    }
+
+    #[test]
+    fn the_task_file_can_be_given_in_every_form() {
+        for args in [
+            &["work.md"][..],
+            &["-f", "work.md"],
+            &["--file", "work.md"],
+            &["--file=work.md"],
+            &["--inline", "--", "work.md"],
+        ] {
+            assert_eq!(run(args).file, PathBuf::from("work.md"), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn only_one_task_file_can_be_given() {
+        let error = Err("Only one task file can be given".to_string());
+        assert_eq!(parse(&["-f", "a.md", "b.md"]), error);
+        assert_eq!(parse(&["--file=a.md", "-f", "b.md"]), error);
+
+        assert_eq!(parse(&["-f"]), Err("-f requires a file path".to_string()));
+    }
+
+    #[test]
+    fn the_environment_variable_is_used_when_no_file_is_given() {
+        let file = |args: &[&str], env_file| match parse_with_env(args, env_file) {
+            Ok(Command::Run(args)) => args.file,
+            result => panic!("{:?}", result),
+        };
+
+        assert_eq!(file(&[], None), PathBuf::from(DEFAULT_FILE_NAME));
+        assert_eq!(file(&[], Some("")), PathBuf::from(DEFAULT_FILE_NAME));
+        assert_eq!(file(&[], Some("env.md")), PathBuf::from("env.md"));
+        assert_eq!(
+            file(&["-f", "arg.md"], Some("env.md")),
+            PathBuf::from("arg.md")
+        );
+        assert_eq!(file(&["ls"], Some("env.md")), PathBuf::from("env.md"));
+    }
+
+    #[test]
+    fn subcommands_are_parsed_with_their_arguments() {
+        let args = run(&["-f", "work.md", "add", "Todo", "Write tests"]);
+        assert_eq!(args.file, PathBuf::from("work.md"));
+        assert_eq!(
+            args.subcommand,
+            Some(Subcommand::Add {
+                list: "Todo".to_string(),
+                description: "Write tests".to_string(),
+            })
+        );
+
+        assert_eq!(
+            run(&["done", "Todo", "2"]).subcommand,
+            Some(Subcommand::Done {
+                list: "Todo".to_string(),
+                number: 2,
+            })
+        );
+        assert_eq!(
+            run(&["rm", "Todo", "1", "--file=work.md"]).subcommand,
+            Some(Subcommand::Remove {
+                list: "Todo".to_string(),
+                number: 1,
+            })
+        );
+        assert_eq!(
+            run(&["ls"]).subcommand,
+            Some(Subcommand::List { list: None })
+        );
+        assert_eq!(run(&["work.md"]).subcommand, None);
+    }
+
+    #[test]
+    fn subcommands_with_bad_arguments_are_reported() {
+        assert_eq!(
+            parse(&["add", "Todo"]),
+            Err("'add' requires a task description".to_string())
+        );
+        assert_eq!(
+            parse(&["done", "Todo", "0"]),
+            Err("'0' is not a valid task number".to_string())
+        );
+        assert_eq!(
+            parse(&["ls", "Todo", "Done"]),
+            Err("Unexpected argument 'Done'".to_string())
+        );
+    }
+
+    #[test]
+    fn options_are_parsed() {
+        assert_eq!(run(&["--full-screen"]).full_screen, Some(true));
+        assert_eq!(run(&["--inline"]).full_screen, Some(false));
+        assert_eq!(run(&[]).full_screen, None);
+        assert_eq!(parse(&["-h"]), Ok(Command::Print(USAGE.to_string())));
+        assert_eq!(
+            parse(&["--nope"]),
+            Err("Unknown option '--nope'".to_string())
+        );
+    }
+}
@@ -1,37 +1,139 @@
 mod app;
+mod cli;
+mod subcommands;
 
-use app::{deserialise, new_tasks_data, serialise, TasksApp};
+use app::{
+    deserialise, load_config, new_tasks_data, recover_file, recovery_path, set_aside, temp_path,
+    TaskFile, TasksApp, TasksError, TerminalGuard,
+};
+use cli::{parse_args, Command};
+use subcommands::run_subcommand;
 
 use crossterm::event::{read, Event, KeyCode};
+use std::io::ErrorKind;
 
-const DEFAULT_LIST_NAME: &str = "Main";
+/// Asks the user a yes or no question, waiting for them to press a key
+///
+/// # Arguments
+///
+/// * `question` - The question to ask
+/// * `default`  - The answer if any key other than y or n is pressed
+fn confirm(question: &str, default: bool) -> bool {
+    println!("{}", question);
+    let _guard = TerminalGuard::new(false).expect("Failed to enable raw terminal mode");
+    match read().expect("failed to read") {
+        Event::Key(key) => match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => true,
+            KeyCode::Char('n') | KeyCode::Char('N') => false,
+            _ => default,
+        },
+        _ => default,
+    }
+}
 
 fn main() {
-    let lists = match deserialise() {
-        Ok(lists) => lists,
-        Err(_) => {
-            println!("Couldn't find a .tasks.md file in this directory, create one? (Y/n)");
-            crossterm::terminal::enable_raw_mode().expect("Failed to enable raw terminal mode");
-            match read().expect("failed to read") {
-                Event::Key(key) => match key.code {
-                    KeyCode::Char('n') | KeyCode::Char('N') => return,
-                    _ => new_tasks_data(DEFAULT_LIST_NAME),
-                },
-                _ => new_tasks_data(DEFAULT_LIST_NAME),
-            }
+    let args = match parse_args() {
+        Ok(Command::Run(args)) => args,
+        Ok(Command::Print(message)) => {
+            println!("{}", message);
+            return;
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let mut config = match load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
         }
     };
-    crossterm::terminal::disable_raw_mode().expect("Failed to disable raw terminal mode");
 
-    let mut app = TasksApp::new(lists);
+    if let Some(full_screen) = args.full_screen {
+        config.set_full_screen(full_screen);
+    }
 
-    match app.run() {
-        Ok(lists) => {
-            if let Some(lists) = lists {
-                serialise(lists).expect("Couldn't serialize")
-            }
+    if let Some(subcommand) = args.subcommand {
+        if let Err(e) = run_subcommand(&args.file, subcommand, config.backups()) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
         }
+        return;
+    }
 
-        Err(e) => eprintln!("Error: {}", e),
+    // Files left over from when the app last exited, along with why they might be wanted
+    let leftovers = [
+        (
+            temp_path(&args.file),
+            "is left over from a save that didn't finish, so it may be incomplete",
+        ),
+        (
+            recovery_path(&args.file),
+            "has changes that weren't saved when tasks was last closed unexpectedly",
+        ),
+    ];
+    for (leftover, reason) in leftovers {
+        if !leftover.exists() {
+            continue;
+        }
+
+        let question = format!(
+            "{} {}. Use it in place of {}? (y/N)",
+            leftover.display(),
+            reason,
+            args.file.display()
+        );
+        // A file that isn't used is kept under another name, in case the answer was a mistake
+        let result = match confirm(&question, false) {
+            true => recover_file(&args.file, &leftover, config.backups()),
+            false => set_aside(&leftover).map(|aside| println!("Kept it as {}", aside.display())),
+        };
+        if let Err(e) = result {
+            eprintln!("Error: Couldn't recover {}: {}", leftover.display(), e);
+            std::process::exit(1);
+        }
+    }
+
+    let mut document = match deserialise(&args.file) {
+        Ok(document) => document,
+        Err(TasksError::Io(e)) if e.kind() == ErrorKind::NotFound => {
+            let question = format!("Couldn't find {}, create one? (Y/n)", args.file.display());
+            match confirm(&question, true) {
+                true => new_tasks_data(config.default_list_name()),
+                false => return,
+            }
+        }
+        Err(e) => {
+            eprintln!("Error: Couldn't read {}: {}", args.file.display(), e);
+            std::process::exit(1);
+        }
     };
+
+    // The app always shows a list, so one is added to a file without any, such as an empty one
+    if document.lists.is_empty() {
+        let question = format!(
+            "{} has no lists in it, add one named {}? (Y/n)",
+            args.file.display(),
+            config.default_list_name()
+        );
+        match confirm(&question, true) {
+            true => document.add_list(config.default_list_name()),
+            false => {
+                let e = TasksError::NoLists;
+                eprintln!("Error: Couldn't open {}: {}", args.file.display(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let file = TaskFile::new(&args.file, document, config.backups());
+    let mut app = TasksApp::new(file, &config);
+
+    if let Err(e) = app.run() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
 }
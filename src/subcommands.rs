@@ -0,0 +1,169 @@
+use std::path::Path;
+
+use crate::app::{deserialise, serialise, List, Task};
+use crate::cli::Subcommand;
+
+/// Runs the given subcommand against the task file
+///
+/// # Arguments
+///
+/// * `path`       - The path of the task file
+/// * `subcommand` - The subcommand to run
+/// * `backups`    - How many previous versions of the task file to keep when it is written
+///
+/// # Returns
+///
+/// An error message if the file couldn't be read or written, or if the list or task the
+/// subcommand refers to doesn't exist
+pub fn run_subcommand(path: &Path, subcommand: Subcommand, backups: usize) -> Result<(), String> {
+    let mut document =
+        deserialise(path).map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+    let lists = &mut document.lists;
+
+    match subcommand {
+        Subcommand::Add { list, description } => {
+            find_list(lists, &list)?
+                .add_task(description)
+                .map_err(|e| e.to_string())?;
+        }
+        Subcommand::Done { list, number } => {
+            let list = find_list(lists, &list)?;
+            let index = task_index(list, number)?;
+            let completed = list
+                .tasks_iter()
+                .nth(index)
+                .map(|task| task.status())
+                .unwrap_or_default();
+
+            if !completed {
+                list.toggle_task(index).map_err(|e| e.to_string())?;
+            }
+        }
+        Subcommand::Remove { list, number } => {
+            let list = find_list(lists, &list)?;
+            let index = task_index(list, number)?;
+            list.delete_task(index).map_err(|e| e.to_string())?;
+        }
+        Subcommand::List { list } => {
+            match list {
+                Some(name) => print_list(find_list(lists, &name)?),
+                None => {
+                    for (i, list) in lists.iter().enumerate() {
+                        if i > 0 {
+                            println!();
+                        }
+                        print_list(list);
+                    }
+                }
+            }
+            return Ok(());
+        }
+    }
+
+    serialise(path, &document, backups)
+        .map_err(|e| format!("Couldn't write {}: {}", path.display(), e))
+}
+
+/// Finds the list with the given name. An exact match is preferred, otherwise a case insensitive
+/// match is used
+///
+/// # Arguments
+///
+/// * `lists` - The lists to search
+/// * `name`  - The name of the list to find
+fn find_list<'a>(lists: &'a mut [List], name: &str) -> Result<&'a mut List, String> {
+    let index = lists
+        .iter()
+        .position(|list| list.name() == name)
+        .or_else(|| {
+            lists
+                .iter()
+                .position(|list| list.name().to_lowercase() == name.to_lowercase())
+        });
+
+    match index {
+        Some(index) => Ok(&mut lists[index]),
+        None => Err(format!("There is no list called '{}'", name)),
+    }
+}
+
+/// Converts a task number as shown by `tasks ls` into an index into the list
+///
+/// # Arguments
+///
+/// * `list`   - The list the task is in
+/// * `number` - The task number, starting at 1
+fn task_index(list: &List, number: usize) -> Result<usize, String> {
+    if number == 0 || number > list.length() {
+        return Err(format!(
+            "'{}' only has {} task{}, there is no task {}",
+            list.name(),
+            list.length(),
+            if list.length() == 1 { "" } else { "s" },
+            number
+        ));
+    }
+
+    Ok(number - 1)
+}
+
+/// Prints a list and its numbered tasks to the stdout, with subtasks indented under their parents
+///
+/// # Arguments
+///
+/// * `list` - The list to print
+fn print_list(list: &List) {
+    println!("{}", list.name());
+    for (i, task) in list.tasks_iter().enumerate() {
+        println!("{}", task_line(i + 1, task));
+    }
+}
+
+/// Returns a task as it is printed by `tasks ls`
+///
+/// # Arguments
+///
+/// * `number` - The number of the task, starting at 1
+/// * `task`   - The task to print
+fn task_line(number: usize, task: &Task) -> String {
+    format!(
+        "{:>3} {}[{}] {}",
+        number,
+        "  ".repeat(task.depth()),
+        match task.status() {
+            true => "x",
+            false => " ",
+        },
+        task.text()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subtasks_are_printed_indented_under_their_parent() {
+        let mut list = List::new("Main").unwrap();
+        for description in ["Parent", "Child", "Grandchild"] {
+            list.add_task(description).unwrap();
+        }
+        list.indent_task(1);
+        list.indent_task(2);
+        list.indent_task(2);
+
+        let lines: Vec<String> = list
+            .tasks_iter()
+            .enumerate()
+            .map(|(i, task)| task_line(i + 1, task))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "  1 [ ] Parent",
+                "  2   [ ] Child",
+                "  3     [ ] Grandchild"
+            ]
+        );
+    }
+}